@@ -1,34 +1,282 @@
-use crate::deck::Deck;
-use crate::hand::Hand;
-use std::cmp::max;
-use std::ops::ControlFlow;
+use crate::combinations::Combinations;
+use crate::deck::{self, Card, CardSet, Deck, Rank};
+use crate::hand::{Category, Hand, HandFormat, Strength};
+use crate::lookup::LookupEvaluator;
+use crate::variant::Variant;
+use std::cmp::{max, Ordering};
+use std::fmt::Display;
 
 type Suit = u16;
 
+fn rank(index: usize) -> Rank {
+    Rank::ALL[index]
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnalyseError {
+    // A hand is made of five cards, so fewer cannot be analysed
+    NotEnoughCards(usize),
+    // Bits that do not belong to any of the 52 cards
+    UnknownCards(Deck),
+    // Cards the variant removes from the deck, like the 2s to 5s of short deck
+    DeadCards(Deck),
+    // Boards are described from the flop to the river
+    WrongBoardSize(usize),
+}
+
+impl Display for AnalyseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnalyseError::NotEnoughCards(count) => write!(f, "need at least 5 cards to form a hand, got {}", count),
+            AnalyseError::UnknownCards(deck) => write!(f, "deck contains bits outside of the 52 cards: {:#x}", deck),
+            AnalyseError::DeadCards(deck) => write!(f, "deck contains cards removed from the variant: {:#x}", deck),
+            AnalyseError::WrongBoardSize(size) => write!(f, "board must have 3 to 5 cards, got {}", size),
+        }
+    }
+}
+
+impl std::error::Error for AnalyseError {}
+
+// Best five card hand out of any deck of at least five cards.
+// Panics on decks the analysis rejects, see try_analyse.
 pub fn analyse(deck: Deck) -> Hand {
+    analyse_variant(deck, Variant::Holdem)
+}
+
+pub fn analyse_variant(deck: Deck, variant: Variant) -> Hand {
+    try_analyse_variant(deck, variant).unwrap_or_else(|error| panic!("{}", error))
+}
+
+// Strengths of many Hold'em decks in one pass, identical to analyse(deck).strength() for each.
+// Decks of 5 to 7 cards go through the batched lookup tables, any others through the analyser.
+pub fn analyse_batch(decks: &[Deck], strengths: &mut [Strength]) {
+    assert_eq!(decks.len(), strengths.len(), "Need one strength for every deck");
+    let lookup = LookupEvaluator::new();
+    let in_tables = |deck: &Deck| (5..=7).contains(&deck.count_ones());
+
+    if decks.iter().all(in_tables) {
+        return lookup.evaluate_batch(decks, strengths);
+    }
+    for (deck, strength) in decks.iter().zip(strengths.iter_mut()) {
+        *strength = match in_tables(deck) {
+            true => lookup.evaluate(*deck),
+            false => analyse(*deck).strength(),
+        };
+    }
+}
+
+pub fn try_analyse(deck: Deck) -> Result<Hand, AnalyseError> {
+    try_analyse_variant(deck, Variant::Holdem)
+}
+
+// Works for the five cards of a flop, the six of a turn, seven card stud or any larger deck
+pub fn try_analyse_variant(deck: Deck, variant: Variant) -> Result<Hand, AnalyseError> {
+    if deck & !deck::FULL_DECK != 0 {
+        return Err(AnalyseError::UnknownCards(deck & !deck::FULL_DECK));
+    }
+    if deck & variant.dead_cards().to_deck() != 0 {
+        return Err(AnalyseError::DeadCards(deck & variant.dead_cards().to_deck()));
+    }
+    if deck.count_ones() < 5 {
+        return Err(AnalyseError::NotEnoughCards(deck.count_ones() as usize));
+    }
+
     let suits = split_suits(deck);
     let merged = merge_suits(&suits);
-    run_analysis(&suits, merged)
+    Ok(run_analysis(&suits, merged, variant))
+}
+
+// Result of an analysis that keeps track of the physical cards.
+// It compares like its hand under the category order of its variant.
+// Analyses of different variants are not comparable, debug builds panic on them.
+#[derive(Debug, Clone)]
+pub struct Analysis {
+    pub hand: Hand,
+    // The five cards forming the hand from the most to the least significant one
+    pub best: [Card; 5],
+    pub unused: CardSet,
+    pub variant: Variant,
+}
+
+impl Analysis {
+    pub fn best_set(&self) -> CardSet {
+        self.best.iter().copied().collect()
+    }
 }
 
-fn split_suits(deck: Deck) -> [Suit; 4] {
+impl PartialEq for Analysis {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Analysis {}
+
+impl Ord for Analysis {
+    fn cmp(&self, other: &Self) -> Ordering {
+        debug_assert_eq!(self.variant, other.variant, "Cannot compare analyses of different variants");
+        self.variant.compare(&self.hand, &other.hand)
+    }
+}
+
+impl PartialOrd for Analysis {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for Analysis {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} [", self.hand.format_variant(HandFormat::Short, self.variant))?;
+        for (i, card) in self.best.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", card)?;
+        }
+        write!(f, "]")
+    }
+}
+
+pub fn analyse_detailed(deck: Deck) -> Analysis {
+    analyse_detailed_variant(deck, Variant::Holdem)
+}
+
+pub fn analyse_detailed_variant(deck: Deck, variant: Variant) -> Analysis {
+    let hand = analyse_variant(deck, variant);
+    let cards = CardSet::from_deck(deck);
+    let mut unused = cards;
+    let best = hand.ranks_variant(variant).map(|rank| {
+        let card = unused.iter()
+            .filter(|card| hand.suit().is_none_or(|suit| card.suit() == suit))
+            .find(|card| card.rank() == rank)
+            .unwrap();
+        unused.remove(card);
+        card
+    });
+
+    Analysis { hand, best, unused, variant }
+}
+
+// What a flop, turn or river looks like to the players, seen from the board alone
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoardTexture {
+    // Two or more cards of a rank
+    pub paired: bool,
+    // Three or more cards of a rank
+    pub trips: bool,
+    // All cards of one suit
+    pub monotone: bool,
+    // Exactly two suits
+    pub two_tone: bool,
+    // No two cards of the same suit
+    pub rainbow: bool,
+    // Two cards of neighbouring ranks, the ace neighbours both the king and the two
+    pub connected: bool,
+    // Neither a flush, nor a straight, nor draws to them are possible
+    pub dry: bool,
+    // At least two of: a flush or flush draw, a straight and connected cards
+    pub wet: bool,
+    // More than half of the cards are ten or higher
+    pub broadway_heavy: bool,
+    // Top cards of the straights that two hole cards can complete
+    pub straights: Vec<Rank>,
+    // Suits with enough cards on the board for a flush
+    pub flushes: Vec<deck::Suit>,
+    // Best hand any two hole cards make
+    pub nuts: Hand,
+    // Whether some runout of the streets to come makes a better hand the nuts
+    pub nuts_can_change: bool,
+}
+
+// Rank masks of the straights with their top cards, the wheel first
+fn straight_windows() -> impl Iterator<Item = (Suit, Rank)> {
+    let (wheel, wheel_top) = Variant::Holdem.wheel();
+    std::iter::once((wheel, wheel_top)).chain((0..9).map(|i| (0x1f << i, rank(i + 4))))
+}
+
+pub fn board_texture(board: Deck) -> Result<BoardTexture, AnalyseError> {
+    if board & !deck::FULL_DECK != 0 {
+        return Err(AnalyseError::UnknownCards(board & !deck::FULL_DECK));
+    }
+    let size = board.count_ones();
+    if !(3..=5).contains(&size) {
+        return Err(AnalyseError::WrongBoardSize(size as usize));
+    }
+
+    let suits = split_suits(board);
+    let ranks = merge_suits(&suits);
+    let max_rank_count = (0..13).map(|r| suits.iter().filter(|suit| *suit & (1 << r) != 0).count()).max().unwrap();
+    let suit_count = suits.iter().filter(|suit| **suit != 0).count();
+    let more_cards = size < 5;
+
+    let straights: Vec<Rank> = straight_windows()
+        .filter(|(window, _)| (ranks & window).count_ones() >= 3)
+        .map(|(_, top)| top)
+        .collect();
+    let flushes: Vec<deck::Suit> = (0..4)
+        .filter(|s| suits[*s].count_ones() >= 3)
+        .map(|s| deck::Suit::ALL[s])
+        .collect();
+    let flush_draw = more_cards && suits.iter().any(|suit| suit.count_ones() >= 2);
+    let straight_draw = more_cards && straight_windows().any(|(window, _)| (ranks & window).count_ones() >= 2);
+    let connected = ranks & (ranks >> 1) != 0 || ranks & 0x1001 == 0x1001;
+    let broadway = (ranks >> Rank::Ten.index()).count_ones();
+
+    let flushy = !flushes.is_empty() || flush_draw;
+    let wet = [flushy, !straights.is_empty(), connected].iter().filter(|feature| **feature).count() >= 2;
+
+    // The nuts never get worse as cards come, so only complete runouts to the river need a look
+    let (nuts, strength) = best_holding(board);
+    let nuts_can_change = strength < Strength::MAX
+        && Combinations::new(deck::FULL_DECK & !board, 5 - size).any(|runout| best_holding(board | runout).1 > strength);
+
+    Ok(BoardTexture {
+        paired: max_rank_count >= 2,
+        trips: max_rank_count >= 3,
+        monotone: suit_count == 1,
+        two_tone: suit_count == 2,
+        rainbow: suit_count as u32 == size,
+        connected,
+        dry: !flushy && straights.is_empty() && !straight_draw,
+        wet,
+        broadway_heavy: broadway * 2 > size,
+        straights,
+        flushes,
+        nuts: analyse(nuts),
+        nuts_can_change,
+    })
+}
+
+// Strongest board and hole card combination out of all two card holdings
+fn best_holding(board: Deck) -> (Deck, Strength) {
+    let lookup = LookupEvaluator::new();
+    Combinations::new(deck::FULL_DECK & !board, 2)
+        .map(|hole| (board | hole, lookup.evaluate(board | hole)))
+        .max_by_key(|(_, strength)| *strength)
+        .unwrap()
+}
+
+// Rank masks of the clubs, hearts, spades and diamonds of a deck
+pub(crate) fn split_suits(deck: Deck) -> [Suit; 4] {
     let mut suits: [Suit; 4] = [0; 4];
 
     for (i, suit) in suits.iter_mut().enumerate() {
-        *suit = (deck >> (13 * i)) as Suit;
+        *suit = (deck >> (13 * i) & 0x1fff) as Suit;
     }
 
-    return suits;
+    suits
 }
 
-fn merge_suits(suits: &[Suit; 4]) -> Suit {
+// Rank mask of every rank present in any suit
+pub(crate) fn merge_suits(suits: &[Suit; 4]) -> Suit {
     let mut merged: Suit = 0;
 
     for suit in suits {
         merged |= suit;
     }
 
-    return merged;
+    merged
 }
 
 #[derive(Debug)]
@@ -37,8 +285,10 @@ struct Data {
     numquads: u8,
     numsets: u8,
     numpairs: u8,
-    straightflush: Option<u8>,
+    // Top card and suit of the best straight flush
+    straightflush: Option<(u8, u8)>,
     straight: Option<u8>,
+    // Suit with the best flush when more than one suit has five cards
     flushsuit: Option<u8>,
 }
 
@@ -56,44 +306,31 @@ impl Data {
     }
 }
 
-fn run_analysis(suits: &[Suit; 4], merged: Suit) -> Hand {
-    // auto b = std::chrono::high_resolution_clock().now();
-    
-    let mut data = analyse_suits_separately(&suits);
-    // println!("{:?}", data);
-
-    if let Some(hand) = check_straightflush(&data) {
-        return hand;
-    } else if let Some(hand) = check_quads(&data) {
-        return hand;
-    } else if let Some(hand) = check_fullhouse(&data) {
-        return hand;
-    } else if let Some(hand) = check_flush(&data, &suits) {
-        return hand;
-    }
-
-    analyse_merged_suits(merged, &mut data);
-   
-    if let Some(hand) = check_straight(&data) {
-        return hand;
-    } else if let Some(hand) = check_set(&data) {
-        return hand;
-    } else if let Some(hand) = check_pairs(&data) {
-        return hand;
-    } else {
-        let mut cards: Vec<u8> = vec!();
-
-        data.values.iter().enumerate().rev().for_each(|(i, count)| {
-            if *count > 0 {
-                cards.push(i as u8);
-            }
-        });
-
-        Hand::highcard(cards.as_slice()[0..5].try_into().unwrap())
+fn run_analysis(suits: &[Suit; 4], merged: Suit, variant: Variant) -> Hand {
+    let wheel = variant.wheel();
+    let mut data = analyse_suits_separately(suits, wheel);
+
+    for category in variant.category_order().iter().rev() {
+        let hand = match category {
+            Category::StraightFlush => check_straightflush(&data),
+            Category::Quads => check_quads(&data),
+            Category::FullHouse => check_fullhouse(&data),
+            Category::Flush => check_flush(&data, suits),
+            Category::Straight => {
+                analyse_merged_suits(merged, &mut data, wheel);
+                check_straight(&data)
+            },
+            Category::Set => check_set(&data),
+            Category::TwoPairs | Category::Pair => check_pairs(&data),
+            Category::HighCard => Some(check_highcard(&data)),
+        };
+
+        if let Some(hand) = hand {
+            return hand;
+        }
     }
 
-    // auto e = std::chrono::high_resolution_clock().now();
-    // std::cout << std::dec << std::chrono::duration_cast<std::chrono::nanoseconds>(e-b).count() << std::endl;
+    unreachable!("every deck has at least a high card")
 }
 
 fn analyse_straight(suit: Suit, field: &mut Option<u8>, mask: Suit, top: u8) {
@@ -105,16 +342,17 @@ fn analyse_straight(suit: Suit, field: &mut Option<u8>, mask: Suit, top: u8) {
     }
 }
 
-fn analyse_suits_separately(suits: &[Suit; 4]) -> Data {
+fn analyse_suits_separately(suits: &[Suit; 4], (wheel, wheel_top): (Suit, Rank)) -> Data {
     let mut data = Data::new();
 
     for (s, suit) in suits.iter().enumerate() {
         let mut count = 0;
+        let mut straightflush = None;
 
         for c in 0..13 {
 
             if c < 9 {
-                analyse_straight(*suit, &mut data.straightflush, 0x1f << c, c + 4);
+                analyse_straight(*suit, &mut straightflush, 0x1f << c, c + 4);
             }
 
             if ((0x1 << c) & *suit) > 0 {
@@ -136,49 +374,55 @@ fn analyse_suits_separately(suits: &[Suit; 4]) -> Data {
             }
         }
 
-        if count >= 5 {
+        if count >= 5 && data.flushsuit.is_none_or(|best| top_five(*suit) > top_five(suits[best as usize])) {
             data.flushsuit = Some(s as u8);
         }
 
-        analyse_straight(*suit, &mut data.straightflush, 0x100f, 3);
+        analyse_straight(*suit, &mut straightflush, wheel, wheel_top.index());
+        if let Some(top) = straightflush {
+            if data.straightflush.is_none_or(|(best, _)| top > best) {
+                data.straightflush = Some((top, s as u8));
+            }
+        }
     }
 
     data
 }
 
-fn analyse_merged_suits(merged: Suit, data: &mut Data) {
+fn analyse_merged_suits(merged: Suit, data: &mut Data, (wheel, wheel_top): (Suit, Rank)) {
     for v in 0..9 {
         analyse_straight(merged, &mut data.straight, 0x1f << v, v + 4);
     }
 
-    analyse_straight(merged, &mut data.straight, 0x100f, 3);
+    analyse_straight(merged, &mut data.straight, wheel, wheel_top.index());
 }
 
 fn check_straightflush(data: &Data) -> Option<Hand> {
-    if let (Some(straightflush), Some(flushsuit)) = (data.straightflush, data.flushsuit) {
-        return Some(Hand::straightflush(straightflush, flushsuit));
+    if let Some((top, suit)) = data.straightflush {
+        return Some(Hand::straightflush(rank(top as usize), deck::Suit::ALL[suit as usize]));
     } 
 
     None
 }
 
 fn check_quads(data: &Data) -> Option<Hand> {
-    if data.numquads == 1 {
-        let mut value = 0;
-        let mut kicker = 0;
+    if data.numquads > 0 {
+        let mut value: Option<usize> = None;
+        let mut kicker: Option<usize> = None;
 
         for (i, count) in data.values.iter().enumerate().rev() {
             match count {
-                4 => value = i as u8,
-                _ => kicker = max(kicker, i as u8),
+                0 => {},
+                4 if value.is_none() => value = Some(i),
+                _ => kicker = kicker.or(Some(i)),
             }
 
-            if value > 0 && kicker > 0 {
+            if value.is_some() && kicker.is_some() {
                 break;
             }
         }
 
-        return Some(Hand::quads(value, kicker));
+        return Some(Hand::quads(rank(value.unwrap()), rank(kicker.unwrap())));
 
     } 
 
@@ -188,44 +432,64 @@ fn check_quads(data: &Data) -> Option<Hand> {
 fn check_fullhouse(data: &Data) -> Option<Hand> {
     if data.numsets > 0 && data.numsets + data.numpairs > 1 {
 
-        let mut set: Option<u8> = None;
-        let mut pair: Option<u8> = None;
+        let mut set: Option<usize> = None;
+        let mut pair: Option<usize> = None;
 
         for (i, count) in data.values.iter().enumerate().rev() {
             if *count == 3 && set.is_none() {
-                set = Some(i as u8);
+                set = Some(i);
             } else if *count > 1 && pair.is_none() {
-                pair = Some(i as u8);
+                pair = Some(i);
             }
         }
 
-        return Some(Hand::fullhouse(set.unwrap(), pair.unwrap()));
+        return Some(Hand::fullhouse(rank(set.unwrap()), rank(pair.unwrap())));
     }
 
     None
 }
 
 fn check_flush(data: &Data, suits: &[Suit; 4]) -> Option<Hand> {
-    if data.flushsuit.is_some() {
-        let mut cards: Vec<u8> = vec!();
-        let suit = suits[data.flushsuit.unwrap() as usize];
+    if let Some(flushsuit) = data.flushsuit {
+        let mut cards: Vec<Rank> = vec!();
+        let suit = top_five(suits[flushsuit as usize]);
 
         for i in (0..13).rev() {
             let mask = (1 as Suit) << i;
             if suit & mask > 0 {
-                cards.push(i);
+                cards.push(rank(i));
             }
         }
 
-        return Some(Hand::flush(cards[cards.len()-5..].try_into().unwrap(), data.flushsuit.unwrap()));
+        return Some(Hand::flush(&cards, deck::Suit::ALL[flushsuit as usize]));
     }
 
     None
 }
 
+// The five highest cards of a suit, which compare like their mask
+fn top_five(mut suit: Suit) -> Suit {
+    while suit.count_ones() > 5 {
+        suit &= suit - 1;
+    }
+    suit
+}
+
+fn check_highcard(data: &Data) -> Hand {
+    let mut cards: Vec<Rank> = vec!();
+
+    data.values.iter().enumerate().rev().for_each(|(i, count)| {
+        if *count > 0 {
+            cards.push(rank(i));
+        }
+    });
+
+    Hand::highcard(&cards[0..5])
+}
+
 fn check_straight(data: &Data) -> Option<Hand> {
     if let Some(top) = data.straight {
-        return Some(Hand::straight(top));
+        return Some(Hand::straight(rank(top as usize)));
     } 
 
     None
@@ -233,20 +497,14 @@ fn check_straight(data: &Data) -> Option<Hand> {
 
 fn check_set(data: &Data) -> Option<Hand> {
     if data.numsets > 0 {
-        let mut set: u8 = 0;
-        let mut kickers: [u8; 2] = [0; 2];
+        let mut set = Rank::Two;
+        let mut kickers: Vec<Rank> = vec!();
 
         for (i, value) in data.values.iter().enumerate().rev() {
 
             match value {
-                3 => set = i as u8,
-                1 => match kickers[0] {
-                    0 => kickers[0] = i as u8,
-                    _ => match kickers[1] {
-                        0 => kickers[1] = i as u8,
-                        _ => {},
-                    }
-                }
+                3 => set = rank(i),
+                1 if kickers.len() < 2 => kickers.push(rank(i)),
                 _ => {},
             }
         }
@@ -261,15 +519,15 @@ fn check_set(data: &Data) -> Option<Hand> {
 fn check_pairs(data: &Data) -> Option<Hand> {
     if data.numpairs > 0 {
 
-        let mut pairs: Vec<u8> = vec!();
-        let mut kickers: Vec<u8> = vec!();
+        let mut pairs: Vec<Rank> = vec!();
+        let mut kickers: Vec<Rank> = vec!();
 
         for (i, count) in data.values.iter().enumerate().rev() {
-            let numpairs = match data.numpairs > 1 { true => 2, false => 1};
-            if *count > 1 && pairs.len() < numpairs as usize {
-                pairs.push(i as u8);
-            } else if *count >= 1 && kickers.len() < 5 - (2 * numpairs) as usize {
-                kickers.push(i as u8);
+            let numpairs = if data.numpairs > 1 { 2 } else { 1 };
+            if *count > 1 && pairs.len() < numpairs {
+                pairs.push(rank(i));
+            } else if *count >= 1 && kickers.len() < 5 - 2 * numpairs {
+                kickers.push(rank(i));
             }
         }
 
@@ -283,18 +541,223 @@ fn check_pairs(data: &Data) -> Option<Hand> {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::hand::Hand;
-    use crate::deck;
+    use crate::combinations::Combinations;
+    use crate::hand::{Hand, Strength};
+    use crate::deck::{self, Rank::*, Suit};
+    use crate::evaluator::{cross_check, random_decks};
 
     #[test]
     fn test_straightflush() {
-        assert_eq!(super::analyse(0b1111111 as deck::Deck), Hand::straightflush(6, 0));
+        assert_eq!(super::analyse(0b1111111 as deck::Deck), Hand::straightflush(Eight, Suit::Clubs));
     }
 
     #[test]
     fn test_pair() {
-        let deck = deck::to_deck(&["Ad", "Ac", "7d", "8c", "Th", "9s", "3s"]);
+        let deck = deck::to_deck(&["Ad", "Ac", "7d", "8c", "Th", "9s", "3s"]).unwrap();
+        let hand = analyse(deck);
+        assert_eq!(hand, Hand::pairs(&[Ace], &[Ten, Nine, Eight]));
+    }
+
+    #[test]
+    fn test_all_five_card_hands() {
+        let mut counts = vec![0u32; 7463];
+        for deck in Combinations::new(deck::FULL_DECK, 5) {
+            counts[analyse(deck).strength().value() as usize] += 1;
+        }
+
+        let mut weaker = 0;
+        for value in 1..=7462 {
+            let strength = Strength::new(value).unwrap();
+            assert_eq!(weaker as f64 / 2598960.0, strength.percentile(), "{}", Hand::from_strength(strength));
+            weaker += counts[value as usize];
+        }
+        assert_eq!(weaker, 2598960);
+    }
+
+    #[test]
+    fn test_not_enough_cards() {
+        let deck = deck::to_deck(&["Ad", "Ac", "7d", "8c"]).unwrap();
+        assert_eq!(try_analyse(deck), Err(AnalyseError::NotEnoughCards(4)));
+        assert_eq!(try_analyse(0), Err(AnalyseError::NotEnoughCards(0)));
+        assert_eq!(try_analyse(0x1f | 1 << 60), Err(AnalyseError::UnknownCards(1 << 60)));
+        assert_eq!(AnalyseError::NotEnoughCards(4).to_string(), "need at least 5 cards to form a hand, got 4");
+    }
+
+    #[test]
+    #[should_panic(expected = "need at least 5 cards to form a hand, got 3")]
+    fn test_analyse_panics_on_small_decks() {
+        analyse(0b111);
+    }
+
+    #[test]
+    fn test_any_number_of_cards() {
+        let best_subset = |deck: Deck| Combinations::new(deck, 5).map(analyse).max().unwrap();
+        let mut rng = deck::seeded_rng(17);
+        for size in 5..=12 {
+            assert_eq!(cross_check(&analyse, &best_subset, random_decks(&mut rng, size, 300)), Ok(300));
+        }
+
+        let flop = deck::to_deck(&["Ad", "Kc", "7d", "7c", "2h"]).unwrap();
+        assert_eq!(analyse(flop), Hand::pairs(&[Seven], &[Ace, King, Two]));
+        let turn = flop | deck::to_deck(&["Ah"]).unwrap();
+        assert_eq!(analyse(turn), Hand::pairs(&[Ace, Seven], &[King]));
+    }
+
+    #[test]
+    fn test_batch_matches_single_analysis() {
+        let mut rng = deck::seeded_rng(5);
+        let decks: Vec<Deck> = (0..500).map(|i| random_decks(&mut rng, 5 + i % 5, 1).next().unwrap()).collect();
+        let mut strengths = vec![Strength::MIN; decks.len()];
+        analyse_batch(&decks, &mut strengths);
+        assert!(decks.iter().zip(&strengths).all(|(deck, strength)| analyse(*deck).strength() == *strength));
+
+        let sevens: Vec<Deck> = random_decks(&mut rng, 7, 100).collect();
+        analyse_batch(&sevens, &mut strengths[..100]);
+        assert!(sevens.iter().zip(&strengths).all(|(deck, strength)| analyse(*deck).strength() == *strength));
+    }
+
+    #[test]
+    fn test_large_decks() {
+        let deck = deck::to_deck(&["2d", "2c", "2h", "2s", "9s", "9d", "9h", "9c"]).unwrap();
+        assert_eq!(analyse(deck), Hand::quads(Nine, Two));
+
+        let deck = deck::to_deck(&["Ah", "Jh", "9h", "7h", "3h", "5c", "6c", "7c", "8c", "9c"]).unwrap();
+        assert_eq!(analyse(deck), Hand::straightflush(Nine, Suit::Clubs));
+
+        let deck = deck::to_deck(&["Ah", "Jh", "9h", "7h", "3h", "Kc", "Qc", "Tc", "8c", "6c", "5c"]).unwrap();
         let hand = analyse(deck);
-        assert_eq!(hand, Hand::pairs(&vec![12], &vec![8, 7, 6]));
+        assert_eq!(hand, Hand::flush(&[Ace, Jack, Nine, Seven, Three], Suit::Hearts));
+        assert_eq!(hand.suit(), Some(Suit::Hearts));
+    }
+
+    #[test]
+    fn test_dry_board() {
+        let texture = board_texture(deck::to_deck(&["Kd", "7c", "2h"]).unwrap()).unwrap();
+        assert!(texture.dry && !texture.wet);
+        assert!(texture.rainbow && !texture.two_tone && !texture.monotone);
+        assert!(!texture.paired && !texture.connected && !texture.broadway_heavy);
+        assert!(texture.straights.is_empty() && texture.flushes.is_empty());
+        assert_eq!(texture.nuts, Hand::set(King, &[Seven, Two]));
+        assert!(texture.nuts_can_change);
+    }
+
+    #[test]
+    fn test_wet_board() {
+        let texture = board_texture(deck::to_deck(&["Jh", "Th", "9h"]).unwrap()).unwrap();
+        assert!(texture.wet && !texture.dry);
+        assert!(texture.monotone && texture.connected && texture.broadway_heavy);
+        assert_eq!(texture.straights, vec![Jack, Queen, King]);
+        assert_eq!(texture.flushes, vec![Suit::Hearts]);
+        assert_eq!(texture.nuts, Hand::straightflush(King, Suit::Hearts));
+        assert!(texture.nuts_can_change);
+
+        let texture = board_texture(deck::to_deck(&["Ah", "2c", "3h", "9c"]).unwrap()).unwrap();
+        assert!(texture.two_tone && texture.connected && texture.wet);
+        assert_eq!(texture.straights, vec![Five]);
+    }
+
+    #[test]
+    fn test_paired_boards() {
+        let texture = board_texture(deck::to_deck(&["7s", "7h", "7d"]).unwrap()).unwrap();
+        assert!(texture.paired && texture.trips && texture.rainbow);
+        assert_eq!(texture.nuts, Hand::quads(Seven, Ace));
+        // Quad kings on a KK runout, a straight flush on 8s 9s
+        assert!(texture.nuts_can_change);
+
+        let texture = board_texture(deck::to_deck(&["7s", "7h", "7d", "2c"]).unwrap()).unwrap();
+        assert!(!texture.nuts_can_change);
+        let texture = board_texture(deck::to_deck(&["Ah", "Kh", "Qh"]).unwrap()).unwrap();
+        assert!(!texture.nuts_can_change);
+
+        let river = deck::to_deck(&["Ks", "Kh", "7d", "4c", "2s"]).unwrap();
+        let texture = board_texture(river).unwrap();
+        assert!(texture.paired && !texture.trips && !texture.rainbow && !texture.nuts_can_change);
+        assert_eq!(texture.nuts, Hand::quads(King, Seven));
+
+        assert_eq!(board_texture(0b11), Err(AnalyseError::WrongBoardSize(2)));
+    }
+
+    #[test]
+    fn test_best_five_cards() {
+        let deck = deck::to_deck(&["Ad", "Ac", "7d", "8c", "Th", "9s", "3s"]).unwrap();
+        let analysis = analyse_detailed(deck);
+        assert_eq!(analysis.hand, analyse(deck));
+        assert_eq!(analysis.best_set(), CardSet::from_deck(deck::to_deck(&["Ad", "Ac", "Th", "9s", "8c"]).unwrap()));
+        assert_eq!(analysis.unused, CardSet::from_deck(deck::to_deck(&["7d", "3s"]).unwrap()));
+        assert_eq!(analysis.best.map(|card| card.to_string()), ["Ac", "Ad", "Th", "9s", "8c"]);
+
+        let deck = deck::to_deck(&["Ah", "Jh", "9h", "7h", "6h", "2c", "6c"]).unwrap();
+        let analysis = analyse_detailed(deck);
+        assert!(analysis.best.iter().all(|card| card.suit() == Suit::Hearts));
+        assert_eq!(analysis.best.map(|card| card.rank()), [Ace, Jack, Nine, Seven, Six]);
+        assert_eq!(analysis.unused.len(), 2);
+
+        let deck = deck::to_deck(&["Ad", "2c", "3d", "4c", "5h", "5s", "Kh"]).unwrap();
+        assert_eq!(analyse_detailed(deck).best.map(|card| card.rank()), [Five, Four, Three, Two, Ace]);
+
+        let deck = deck::to_deck(&["Ah", "Jh", "9h", "7h", "6h", "3h", "2h"]).unwrap();
+        assert_eq!(analyse(deck), Hand::flush(&[Ace, Jack, Nine, Seven, Six], Suit::Hearts));
+    }
+
+    #[test]
+    fn test_short_deck_wheel_cards() {
+        let deck = deck::to_deck(&["Ad", "6c", "7d", "8c", "9h", "Ks", "Qs"]).unwrap();
+        let analysis = analyse_detailed_variant(deck, Variant::ShortDeck);
+        assert_eq!(analysis.best.map(|card| card.rank()), [Nine, Eight, Seven, Six, Ace]);
+        assert_eq!(analysis.unused, CardSet::from_deck(deck::to_deck(&["Ks", "Qs"]).unwrap()));
+        assert_eq!(analysis.to_string(), "straight A-9 [9h 8c 7d 6c Ad]");
+    }
+
+    #[test]
+    fn test_analysis_ordering() {
+        let a = analyse_detailed(deck::to_deck(&["As", "Ks", "Qs", "Js", "9d"]).unwrap());
+        let b = analyse_detailed(deck::to_deck(&["Ah", "Kh", "Qh", "Jh", "9c"]).unwrap());
+        let c = analyse_detailed(deck::to_deck(&["2h", "2c", "Qh", "Jh", "9c"]).unwrap());
+        assert_eq!(a, b);
+        assert!(c > a);
+        assert_eq!(c.to_string(), "pair 2 (Q, J, 9) [2c 2h Qh Jh 9c]");
+    }
+
+    #[test]
+    fn test_short_deck_analysis_ordering() {
+        let flush = deck::to_deck(&["Ah", "Jh", "9h", "8h", "6h"]).unwrap();
+        let fullhouse = deck::to_deck(&["6h", "6s", "6d", "7c", "7s"]).unwrap();
+        let short_deck = |deck| analyse_detailed_variant(deck, Variant::ShortDeck);
+        assert!(short_deck(flush) > short_deck(fullhouse));
+        assert!(analyse_detailed(flush) < analyse_detailed(fullhouse));
+    }
+
+    #[test]
+    fn test_quads_kicker() {
+        let deck = deck::to_deck(&["2d", "2c", "2h", "2s", "3s", "4d", "7c"]).unwrap();
+        assert_eq!(analyse(deck), Hand::quads(Two, Seven));
+        let deck = deck::to_deck(&["Ad", "Ac", "Ah", "As", "3s"]).unwrap();
+        assert_eq!(analyse(deck), Hand::quads(Ace, Three));
+    }
+
+    #[test]
+    fn test_short_deck_wheel() {
+        let deck = deck::to_deck(&["Ad", "6c", "7d", "8c", "9h", "Ks", "Qs"]).unwrap();
+        assert_eq!(analyse_variant(deck, Variant::ShortDeck), Hand::straight(Nine));
+        assert_eq!(analyse(deck), Hand::highcard(&[Ace, King, Queen, Nine, Eight]));
+
+        // Fives are not part of a short deck
+        let deck = deck::to_deck(&["5d", "6c", "7d", "8c", "9h"]).unwrap();
+        let five = deck::to_deck(&["5d"]).unwrap();
+        assert_eq!(try_analyse_variant(deck, Variant::ShortDeck), Err(AnalyseError::DeadCards(five)));
+        assert_eq!(try_analyse(deck), Ok(Hand::straight(Nine)));
+    }
+
+    #[test]
+    fn test_short_deck_category_order() {
+        let deck = deck::to_deck(&["Ah", "Jh", "9h", "7h", "6h", "6c", "6d"]).unwrap();
+        assert_eq!(analyse(deck).category(), Category::Flush);
+        let deck = deck::to_deck(&["Ah", "Jh", "9h", "7h", "6h", "6c", "6d", "7c"]).unwrap();
+        assert_eq!(analyse(deck).category(), Category::FullHouse);
+        assert_eq!(analyse_variant(deck, Variant::ShortDeck).category(), Category::Flush);
+
+        let deck = deck::to_deck(&["Ts", "Jh", "Qh", "Kd", "Ac", "Ad", "As"]).unwrap();
+        assert_eq!(analyse_variant(deck, Variant::ShortDeck), Hand::straight(Ace));
+        assert_eq!(analyse_variant(deck, Variant::ShortDeckTripsBeatStraights), Hand::set(Ace, &[King, Queen]));
     }
 }
\ No newline at end of file
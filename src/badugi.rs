@@ -0,0 +1,115 @@
+use crate::analyser::{merge_suits, split_suits};
+use crate::combinations::Combinations;
+use crate::deck::{Card, CardSet, Deck, Rank, FULL_DECK};
+use crate::lowball::low_value;
+use std::cmp::Ordering;
+use std::fmt::Display;
+
+// The cards of a Badugi hand that play: all of different ranks and different suits.
+// More cards beat fewer cards, then the lower hand wins with aces low, so A-2-3-4 of four
+// suits is the best hand. Like the other lowball hands, the better hand compares greater.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Badugi {
+    // From the highest to the lowest card
+    ranks: Vec<Rank>,
+}
+
+impl Badugi {
+    pub fn len(&self) -> usize {
+        self.ranks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranks.is_empty()
+    }
+
+    pub fn ranks(&self) -> &[Rank] {
+        &self.ranks
+    }
+
+    fn from_cards(cards: Deck) -> Self {
+        let mut ranks: Vec<Rank> = CardSet::from_deck(cards).iter().map(Card::rank).collect();
+        ranks.sort_unstable_by_key(|rank| std::cmp::Reverse(low_value(*rank)));
+        Badugi { ranks }
+    }
+}
+
+impl Ord for Badugi {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let low = |hand: &Badugi| hand.ranks.iter().map(|rank| low_value(*rank)).collect::<Vec<_>>();
+        self.len().cmp(&other.len()).then_with(|| low(other).cmp(&low(self)))
+    }
+}
+
+impl PartialOrd for Badugi {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// "4-card badugi 7-4-3-A", only a four card hand is a badugi proper
+impl Display for Badugi {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ranks: Vec<String> = self.ranks.iter().map(|rank| rank.to_string()).collect();
+        write!(f, "{}-card badugi {}", self.len(), ranks.join("-"))
+    }
+}
+
+// Cards of different ranks and suits only
+fn is_valid(cards: Deck) -> bool {
+    let suits = split_suits(cards);
+    suits.iter().all(|suit| suit.count_ones() <= 1) && merge_suits(&suits).count_ones() == cards.count_ones()
+}
+
+// Best Badugi hand among the cards, which are usually the four cards of a player.
+// None for an empty deck or one with bits outside of the 52 cards.
+pub fn badugi(deck: Deck) -> Option<Badugi> {
+    if deck == 0 || deck & !FULL_DECK != 0 {
+        return None;
+    }
+
+    (1..=deck.count_ones().min(4))
+        .rev()
+        .find_map(|size| Combinations::new(deck, size).filter(|cards| is_valid(*cards)).map(Badugi::from_cards).max())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::deck::{to_deck, Rank::*};
+
+    fn hand(cards: &[&str]) -> Badugi {
+        badugi(to_deck(cards).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_four_card_badugi() {
+        let badugi = hand(&["7c", "4h", "3s", "Ad"]);
+        assert_eq!(badugi.to_string(), "4-card badugi 7-4-3-A");
+        assert_eq!(badugi.ranks(), &[Seven, Four, Three, Ace]);
+        assert!(hand(&["4c", "3h", "2s", "Ad"]) > badugi);
+        assert!(hand(&["7c", "5h", "3s", "Ad"]) < badugi);
+        assert!(hand(&["Kc", "Qh", "Js", "Td"]) > hand(&["Ac", "2c", "3h", "4s"]));
+    }
+
+    #[test]
+    fn test_fewer_cards_play() {
+        // A pair and a suited card only leave two cards
+        let three = hand(&["Ac", "2c", "3h", "4s"]);
+        assert_eq!(three.to_string(), "3-card badugi 4-3-A");
+        let two = hand(&["Ac", "Ah", "2c", "2h"]);
+        assert_eq!(two.to_string(), "2-card badugi 2-A");
+        assert_eq!(hand(&["Kc", "Qc", "Jc", "Tc"]).to_string(), "1-card badugi T");
+        assert!(three > two);
+        assert!(hand(&["Kc", "Qh", "Jc", "Th"]) < two);
+    }
+
+    #[test]
+    fn test_best_subset() {
+        // The 8 and the 3 share a suit, dropping the 8 keeps the lower three cards
+        assert_eq!(hand(&["8c", "3c", "2h", "As"]).to_string(), "3-card badugi 3-2-A");
+        assert_eq!(hand(&["8c", "3c", "2h", "As", "5d", "Kd"]).to_string(), "4-card badugi 5-3-2-A");
+        assert_eq!(badugi(0), None);
+        assert_eq!(badugi(1 << 53), None);
+    }
+}
@@ -0,0 +1,222 @@
+use crate::deck::{Deck, FULL_DECK};
+use std::ops::Range;
+
+const fn binomial_table() -> [[u64; 53]; 53] {
+    let mut table = [[0; 53]; 53];
+    let mut n = 0;
+    while n < 53 {
+        table[n][0] = 1;
+        let mut k = 1;
+        while k <= n {
+            table[n][k] = table[n - 1][k - 1] + table[n - 1][k];
+            k += 1;
+        }
+        n += 1;
+    }
+    table
+}
+
+static BINOMIAL: [[u64; 53]; 53] = binomial_table();
+
+// Number of ways to choose k out of n cards, zero when k > n
+pub fn binomial(n: u32, k: u32) -> u64 {
+    assert!(n <= 52, "Cannot choose out of more than 52 cards");
+    match k > n {
+        true => 0,
+        false => BINOMIAL[n as usize][k as usize],
+    }
+}
+
+// Lazily yields every k card subset of the free cards without allocating.
+// Subsets come in colexicographic order, which is the order of their Deck values,
+// so a position in the sequence can be turned into a subset directly and the
+// sequence can be cut into ranges for separate workers.
+#[derive(Debug, Clone)]
+pub struct Combinations {
+    free: Deck,
+    k: u32,
+    current: Deck,
+    position: u64,
+    end: u64,
+}
+
+impl Combinations {
+    pub fn new(free: Deck, k: u32) -> Self {
+        let total = binomial(free.count_ones(), k);
+        Combinations::range(free, k, 0..total)
+    }
+
+    // Only the subsets at the given positions of the full sequence
+    pub fn range(free: Deck, k: u32, range: Range<u64>) -> Self {
+        assert_eq!(free & !FULL_DECK, 0, "Deck contains bits outside of the 52 cards");
+        let total = binomial(free.count_ones(), k);
+        let end = range.end.min(total);
+        let position = range.start.min(end);
+        Combinations {
+            free,
+            k,
+            current: unrank(free, k, position),
+            position,
+            end,
+        }
+    }
+
+    // Positions of the remaining subsets within the full sequence
+    pub fn positions(&self) -> Range<u64> {
+        self.position..self.end
+    }
+
+    // Cuts the remaining subsets into at most parts contiguous ranges of nearly equal size
+    pub fn split(&self, parts: usize) -> Vec<Combinations> {
+        assert!(parts > 0);
+        let len = self.end - self.position;
+        let parts = (parts as u64).min(len).max(1);
+        (0..parts)
+            .map(|i| {
+                let start = self.position + len * i / parts;
+                let end = self.position + len * (i + 1) / parts;
+                Combinations::range(self.free, self.k, start..end)
+            })
+            .collect()
+    }
+}
+
+impl Iterator for Combinations {
+    type Item = Deck;
+
+    fn next(&mut self) -> Option<Deck> {
+        if self.position >= self.end {
+            return None;
+        }
+
+        let combination = self.current;
+        self.position += 1;
+        if self.position < self.end {
+            self.current = successor(self.free, self.k, combination);
+        }
+        Some(combination)
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Deck> {
+        let position = self.position.saturating_add(n as u64);
+        if position >= self.end {
+            self.position = self.end;
+            return None;
+        }
+        self.position = position;
+        self.current = unrank(self.free, self.k, position);
+        self.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.end - self.position) as usize;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for Combinations {}
+
+// Gosper's hack applied to the sparse free card mask: the lowest run of chosen cards
+// is carried one free card higher and the rest of the run drops back to the lowest free cards.
+// Holes outside the mask are filled with ones so the carry skips over them.
+fn successor(free: Deck, k: u32, combination: Deck) -> Deck {
+    let lowest = combination & combination.wrapping_neg();
+    let carried = (combination | !free).wrapping_add(lowest) & free;
+    let mut refill = k - carried.count_ones();
+    let mut next = carried;
+    let mut low = free;
+    while refill > 0 {
+        next |= low & low.wrapping_neg();
+        low &= low - 1;
+        refill -= 1;
+    }
+    next
+}
+
+// Subset at the given position of the colexicographic sequence. Going down from the highest
+// free card, a card is chosen when the subsets made of the cards below it are used up.
+fn unrank(free: Deck, k: u32, mut position: u64) -> Deck {
+    let mut remaining = free;
+    let mut combination = 0;
+
+    for i in (1..=k).rev() {
+        while remaining != 0 {
+            let card = 63 - remaining.leading_zeros();
+            remaining &= !(1 << card);
+            let below = binomial(remaining.count_ones(), i);
+            if below <= position {
+                position -= below;
+                combination |= 1 << card;
+                break;
+            }
+        }
+    }
+
+    combination
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_binomial() {
+        assert_eq!(binomial(52, 5), 2598960);
+        assert_eq!(binomial(48, 5), 1712304);
+        assert_eq!(binomial(52, 26), 495918532948104);
+        assert_eq!(binomial(3, 4), 0);
+        assert_eq!(binomial(0, 0), 1);
+    }
+
+    #[test]
+    fn test_sparse_mask_enumeration() {
+        let free = 0b1011_0110_1101;
+        let all: Vec<Deck> = Combinations::new(free, 3).collect();
+        assert_eq!(all.len() as u64, binomial(8, 3));
+        assert!(all.windows(2).all(|w| w[0] < w[1]));
+        assert!(all.iter().all(|c| c & !free == 0 && c.count_ones() == 3));
+    }
+
+    #[test]
+    fn test_edge_sizes() {
+        assert_eq!(Combinations::new(0b1011, 0).collect::<Vec<_>>(), vec![0]);
+        assert_eq!(Combinations::new(0b1011, 3).collect::<Vec<_>>(), vec![0b1011]);
+        assert_eq!(Combinations::new(0b1011, 4).count(), 0);
+        assert_eq!(Combinations::new(FULL_DECK, 52).collect::<Vec<_>>(), vec![FULL_DECK]);
+        let last = Combinations::new(FULL_DECK, 2).last().unwrap();
+        assert_eq!(last, 0b11 << 50);
+    }
+
+    #[test]
+    fn test_skipping_matches_iteration() {
+        let free = FULL_DECK & !0b1100_0000_0001;
+        let all: Vec<Deck> = Combinations::new(free, 4).collect();
+        for n in [0, 1, 17, 1000, all.len() - 1] {
+            assert_eq!(Combinations::new(free, 4).nth(n), Some(all[n]));
+        }
+        assert_eq!(Combinations::new(free, 4).nth(all.len()), None);
+
+        let mut it = Combinations::new(free, 4);
+        it.nth(9);
+        assert_eq!(it.next(), Some(all[10]));
+        assert_eq!(it.len(), all.len() - 11);
+
+        let sparse = 0b1011_0110_1101;
+        let all: Vec<Deck> = Combinations::new(sparse, 3).collect();
+        assert!((0..all.len()).all(|n| Combinations::new(sparse, 3).nth(n) == Some(all[n])));
+    }
+
+    #[test]
+    fn test_split_covers_sequence() {
+        let free = FULL_DECK & !0b110;
+        let all: Vec<Deck> = Combinations::new(free, 3).collect();
+        let parts = Combinations::new(free, 3).split(7);
+        assert_eq!(parts.len(), 7);
+        assert_eq!(parts[0].positions().start, 0);
+        assert_eq!(parts[6].positions().end, all.len() as u64);
+        let joined: Vec<Deck> = parts.into_iter().flatten().collect();
+        assert_eq!(joined, all);
+
+        assert_eq!(Combinations::new(0b111, 3).split(4).len(), 1);
+    }
+}
@@ -0,0 +1,233 @@
+use crate::deck::{self, Card, CardSet};
+use crate::variant::Variant;
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::fmt::Display;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Street {
+    Preflop,
+    Flop,
+    Turn,
+    River,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DealError {
+    NotEnoughCards { needed: usize, remaining: usize },
+    // The requested cards cannot be dealt on the current street
+    WrongStreet(Street),
+    NoSeats,
+}
+
+impl Display for DealError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DealError::NotEnoughCards { needed, remaining } => write!(f, "need {} cards but only {} are left", needed, remaining),
+            DealError::WrongStreet(street) => write!(f, "cannot deal that on the {:?}", street),
+            DealError::NoSeats => write!(f, "no seats to deal to"),
+        }
+    }
+}
+
+impl std::error::Error for DealError {}
+
+// Deals a single hand from a shuffled deck the way it is done at the table:
+// hole cards one at a time around the table, then a burn card before every street
+pub struct Dealer<R: Rng = StdRng> {
+    rng: R,
+    variant: Variant,
+    // The top of the stub is the last card
+    stub: Vec<Card>,
+    burned: CardSet,
+    board: Vec<Card>,
+    street: Street,
+}
+
+impl Dealer<StdRng> {
+    pub fn with_seed(seed: u64) -> Self {
+        Dealer::new(deck::seeded_rng(seed))
+    }
+}
+
+impl<R: Rng> Dealer<R> {
+    pub fn new(rng: R) -> Self {
+        Dealer::for_variant(rng, Variant::Holdem)
+    }
+
+    pub fn for_variant(rng: R, variant: Variant) -> Self {
+        let mut dealer = Dealer {
+            rng,
+            variant,
+            stub: vec![],
+            burned: CardSet::EMPTY,
+            board: vec![],
+            street: Street::Preflop,
+        };
+        dealer.shuffle();
+        dealer
+    }
+
+    // Collects all cards back and starts a new hand
+    pub fn shuffle(&mut self) {
+        self.stub = deck::shuffle_remaining(self.variant.dead_cards().to_deck(), &mut self.rng);
+        self.burned = CardSet::EMPTY;
+        self.board.clear();
+        self.street = Street::Preflop;
+    }
+
+    // Deals cards_per_seat cards to every seat, one card per round starting with the seat left of the button.
+    // The result is indexed by seat.
+    pub fn deal_hole_cards(&mut self, seats: usize, button: usize, cards_per_seat: usize) -> Result<Vec<CardSet>, DealError> {
+        if seats == 0 {
+            return Err(DealError::NoSeats);
+        }
+        if self.street != Street::Preflop {
+            return Err(DealError::WrongStreet(self.street));
+        }
+        self.ensure_remaining(seats * cards_per_seat)?;
+
+        let mut hands = vec![CardSet::EMPTY; seats];
+        for _ in 0..cards_per_seat {
+            for offset in 1..=seats {
+                let card = self.draw();
+                hands[(button + offset) % seats].insert(card);
+            }
+        }
+        Ok(hands)
+    }
+
+    pub fn deal_flop(&mut self) -> Result<[Card; 3], DealError> {
+        self.deal_street(Street::Preflop, 3)?;
+        Ok(self.board[..3].try_into().unwrap())
+    }
+
+    pub fn deal_turn(&mut self) -> Result<Card, DealError> {
+        self.deal_street(Street::Flop, 1)?;
+        Ok(self.board[3])
+    }
+
+    pub fn deal_river(&mut self) -> Result<Card, DealError> {
+        self.deal_street(Street::Turn, 1)?;
+        Ok(self.board[4])
+    }
+
+    pub fn street(&self) -> Street {
+        self.street
+    }
+
+    pub fn board(&self) -> &[Card] {
+        &self.board
+    }
+
+    pub fn burned(&self) -> CardSet {
+        self.burned
+    }
+
+    // Cards that have not been dealt or burned yet
+    pub fn stub(&self) -> CardSet {
+        self.stub.iter().copied().collect()
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.stub.len()
+    }
+
+    fn deal_street(&mut self, from: Street, count: usize) -> Result<(), DealError> {
+        if self.street != from {
+            return Err(DealError::WrongStreet(self.street));
+        }
+        self.ensure_remaining(count + 1)?;
+
+        let burn = self.draw();
+        self.burned.insert(burn);
+        for _ in 0..count {
+            let card = self.draw();
+            self.board.push(card);
+        }
+        self.street = match from {
+            Street::Preflop => Street::Flop,
+            Street::Flop => Street::Turn,
+            _ => Street::River,
+        };
+        Ok(())
+    }
+
+    fn ensure_remaining(&self, needed: usize) -> Result<(), DealError> {
+        match self.stub.len() {
+            remaining if remaining < needed => Err(DealError::NotEnoughCards { needed, remaining }),
+            _ => Ok(()),
+        }
+    }
+
+    fn draw(&mut self) -> Card {
+        self.stub.pop().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_full_hand() {
+        let mut dealer = Dealer::with_seed(3);
+        let hands = dealer.deal_hole_cards(6, 0, 2).unwrap();
+        let flop = dealer.deal_flop().unwrap();
+        let turn = dealer.deal_turn().unwrap();
+        let river = dealer.deal_river().unwrap();
+        assert_eq!(dealer.street(), Street::River);
+        assert_eq!(dealer.board(), &[flop[0], flop[1], flop[2], turn, river]);
+        assert_eq!(dealer.burned().len(), 3);
+        assert_eq!(dealer.remaining(), 52 - 12 - 3 - 5);
+
+        let board: CardSet = dealer.board().iter().copied().collect();
+        let holes: CardSet = hands.iter().copied().collect();
+        assert!(hands.iter().all(|hand| hand.len() == 2));
+        assert_eq!(holes.len(), 12);
+        assert_eq!((holes | board | dealer.burned() | dealer.stub()).len(), 52);
+    }
+
+    #[test]
+    fn test_deal_rotation() {
+        let mut order = deck::shuffle_remaining(0, &mut deck::seeded_rng(11));
+        let mut dealer = Dealer::with_seed(11);
+        let hands = dealer.deal_hole_cards(3, 1, 2).unwrap();
+
+        // Seat 2 sits left of the button and receives the first and fourth card
+        let mut expected = vec![CardSet::EMPTY; 3];
+        for seat in [2, 0, 1, 2, 0, 1] {
+            expected[seat].insert(order.pop().unwrap());
+        }
+        assert_eq!(hands, expected);
+    }
+
+    #[test]
+    fn test_short_deck_dealing() {
+        let mut dealer = Dealer::for_variant(deck::seeded_rng(5), Variant::ShortDeck);
+        assert_eq!(dealer.remaining(), 36);
+        assert_eq!(dealer.stub(), Variant::ShortDeck.deck());
+        let hands = dealer.deal_hole_cards(4, 0, 2).unwrap();
+        assert!(hands.iter().all(|hand| hand.is_subset(Variant::ShortDeck.deck())));
+    }
+
+    #[test]
+    fn test_deal_out_of_order() {
+        let mut dealer = Dealer::with_seed(0);
+        assert_eq!(dealer.deal_turn(), Err(DealError::WrongStreet(Street::Preflop)));
+        dealer.deal_flop().unwrap();
+        assert_eq!(dealer.deal_hole_cards(2, 0, 2), Err(DealError::WrongStreet(Street::Flop)));
+        assert_eq!(dealer.deal_river(), Err(DealError::WrongStreet(Street::Flop)));
+        assert_eq!(
+            dealer.deal_hole_cards(0, 0, 2).unwrap_err(),
+            DealError::NoSeats
+        );
+
+        dealer.shuffle();
+        assert_eq!(dealer.remaining(), 52);
+        assert_eq!(
+            dealer.deal_hole_cards(23, 0, 3),
+            Err(DealError::NotEnoughCards { needed: 69, remaining: 52 })
+        );
+    }
+}
@@ -1,110 +1,666 @@
-use rand::distributions::Uniform;
-use rand::prelude::Distribution;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::fmt::Display;
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, RangeInclusive, Sub, SubAssign};
+use std::str::FromStr;
 
 pub type Deck = u64;
 
-// const VALUES: [&str; 13] = ["2", "3", "4", "5", "6", "7", "8", "9", "T", "J", "Q", "K", "A"];
-// const SUITS: [&str; 4] = ["C", "H", "S", "D"];
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Rank {
+    Two,
+    Three,
+    Four,
+    Five,
+    Six,
+    Seven,
+    Eight,
+    Nine,
+    Ten,
+    Jack,
+    Queen,
+    King,
+    Ace,
+}
 
-pub fn to_deck<T: AsRef<str>>(cards: &[T]) -> Deck
-{
-    let mut deck: Deck = 0;
-    for card in cards {
-        deck |= (1 << card_from_string(card.as_ref()));
+impl Rank {
+    pub const ALL: [Rank; 13] = [
+        Rank::Two,
+        Rank::Three,
+        Rank::Four,
+        Rank::Five,
+        Rank::Six,
+        Rank::Seven,
+        Rank::Eight,
+        Rank::Nine,
+        Rank::Ten,
+        Rank::Jack,
+        Rank::Queen,
+        Rank::King,
+        Rank::Ace,
+    ];
+
+    pub fn from_index(index: u8) -> Option<Rank> {
+        Rank::ALL.get(index as usize).copied()
+    }
+
+    pub fn index(self) -> u8 {
+        self as u8
+    }
+
+    pub fn from_char(c: char) -> Option<Rank> {
+        Some(match c {
+            '2' => Rank::Two,
+            '3' => Rank::Three,
+            '4' => Rank::Four,
+            '5' => Rank::Five,
+            '6' => Rank::Six,
+            '7' => Rank::Seven,
+            '8' => Rank::Eight,
+            '9' => Rank::Nine,
+            'T' => Rank::Ten,
+            'J' => Rank::Jack,
+            'Q' => Rank::Queen,
+            'K' => Rank::King,
+            'A' => Rank::Ace,
+            _ => return None,
+        })
+    }
+
+    pub fn to_char(self) -> char {
+        b"23456789TJQKA"[self as usize] as char
+    }
+
+    pub fn name(self) -> &'static str {
+        ["Two", "Three", "Four", "Five", "Six", "Seven", "Eight", "Nine", "Ten", "Jack", "Queen", "King", "Ace"][self as usize]
+    }
+
+    pub fn plural(self) -> &'static str {
+        ["Twos", "Threes", "Fours", "Fives", "Sixes", "Sevens", "Eights", "Nines", "Tens", "Jacks", "Queens", "Kings", "Aces"][self as usize]
     }
-    deck
 }
 
-pub fn from_deck(deck: Deck) -> Vec<String> {
-    assert_eq!(deck >> 52, 0);
-    let mut v = vec![];
-    for i in 0..52 {
-        if (deck & 0x1 << i) > 0 {
-            v.push(card_to_string(i));
+impl Display for Rank {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+impl FromStr for Rank {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let c = single_char(s)?;
+        Rank::from_char(c).ok_or(ParseError::UnknownRank(c))
+    }
+}
+
+// Order matches the layout of a Deck: clubs occupy the lowest 13 bits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Suit {
+    Clubs,
+    Hearts,
+    Spades,
+    Diamonds,
+}
+
+impl Suit {
+    pub const ALL: [Suit; 4] = [Suit::Clubs, Suit::Hearts, Suit::Spades, Suit::Diamonds];
+
+    pub fn from_index(index: u8) -> Option<Suit> {
+        Suit::ALL.get(index as usize).copied()
+    }
+
+    pub fn index(self) -> u8 {
+        self as u8
+    }
+
+    pub fn from_char(c: char) -> Option<Suit> {
+        Some(match c.to_ascii_lowercase() {
+            'c' => Suit::Clubs,
+            'h' => Suit::Hearts,
+            's' => Suit::Spades,
+            'd' => Suit::Diamonds,
+            _ => return None,
+        })
+    }
+
+    pub fn to_char(self) -> char {
+        b"chsd"[self as usize] as char
+    }
+
+    pub fn name(self) -> &'static str {
+        ["Clubs", "Hearts", "Spades", "Diamonds"][self as usize]
+    }
+
+    // Accepts both the filled and the outlined card suit symbols
+    pub fn from_symbol(c: char) -> Option<Suit> {
+        Some(match c {
+            '♣' | '♧' => Suit::Clubs,
+            '♥' | '♡' => Suit::Hearts,
+            '♠' | '♤' => Suit::Spades,
+            '♦' | '♢' => Suit::Diamonds,
+            _ => return None,
+        })
+    }
+
+    pub fn to_symbol(self) -> char {
+        ['♣', '♥', '♠', '♦'][self as usize]
+    }
+}
+
+impl Display for Suit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+impl FromStr for Suit {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let c = single_char(s)?;
+        Suit::from_char(c).ok_or(ParseError::UnknownSuit(c))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Card {
+    rank: Rank,
+    suit: Suit,
+}
+
+impl Card {
+    pub fn new(rank: Rank, suit: Suit) -> Self {
+        Card { rank, suit }
+    }
+
+    pub fn rank(self) -> Rank {
+        self.rank
+    }
+
+    pub fn suit(self) -> Suit {
+        self.suit
+    }
+
+    // Position of the card's bit inside a Deck: suit * 13 + rank
+    pub fn from_index(index: u8) -> Option<Card> {
+        Some(Card::new(Rank::from_index(index % 13)?, Suit::from_index(index / 13)?))
+    }
+
+    pub fn index(self) -> u8 {
+        self.suit.index() * 13 + self.rank.index()
+    }
+
+    pub fn to_deck(self) -> Deck {
+        1 << self.index()
+    }
+}
+
+impl Display for Card {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.rank, self.suit)
+    }
+}
+
+impl FromStr for Card {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chars: Vec<char> = s.chars().collect();
+        match chars[..] {
+            [rank, suit] => Ok(Card::new(
+                Rank::from_char(rank).ok_or(ParseError::UnknownRank(rank))?,
+                Suit::from_char(suit).ok_or(ParseError::UnknownSuit(suit))?,
+            )),
+            _ => Err(ParseError::WrongLength(chars.len())),
         }
-    };
-    v
+    }
 }
 
-pub fn card_to_string(value: u8) -> String {
-    String::from(
-        match value % 13 {
-            0 => "2",
-            1 => "3",
-            2 => "4",
-            3 => "5",
-            4 => "6",
-            5 => "7",
-            6 => "8",
-            7 => "9",
-            8 => "T",
-            9 => "J",
-            10 => "Q",
-            11 => "K",
-            12 => "A",
-            _ => panic!(),
-        }) + match value / 13 {
-            0 => "c",
-            1 => "h",
-            2 => "s",
-            3 => "d",
-            _ => panic!(),
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    UnknownRank(char),
+    UnknownSuit(char),
+    // Number of characters found where a single rank, suit or card was expected
+    WrongLength(usize),
+    DuplicateCard(Card),
+    // Wraps the error of the token at the given zero-based position in a list of cards
+    BadToken { position: usize, error: Box<ParseError> },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::UnknownRank(c) => write!(f, "unknown rank '{}'", c),
+            ParseError::UnknownSuit(c) => write!(f, "unknown suit '{}'", c),
+            ParseError::WrongLength(len) => write!(f, "unexpected length {}", len),
+            ParseError::DuplicateCard(card) => write!(f, "duplicate card {}", card),
+            ParseError::BadToken { position, error } => write!(f, "card {}: {}", position + 1, error),
         }
+    }
 }
 
-pub fn card_from_string(s: &str) -> u8 {
-    // let value = VALUES.iter().position(|&r| r == &s[0..1]).unwrap() as u8;
-    // let suit = SUITS.iter().position(|&r| r == &s[0..2]).unwrap() as u8;
-    // (suit * 4) + value
-
-    let value: u8 = match &s[0..1] {
-        "2" => 0,
-        "3" => 1,
-        "4" => 2,
-        "5" => 3,
-        "6" => 4,
-        "7" => 5,
-        "8" => 6,
-        "9" => 7,
-        "T" => 8,
-        "J" => 9,
-        "Q" => 10,
-        "K" => 11,
-        "A" => 12,
-        _ => {panic!()},
-    };
+impl std::error::Error for ParseError {}
 
-    let suit = match &s[1..2].to_lowercase()[..] {
-        "c" => 0,
-        "h" => 1,
-        "s" => 2,
-        "d" => 3,
-        _ => {panic!()},
-    };
+// Problems with the hole cards and the board handed to a hand evaluation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HoleBoardError {
+    WrongHoleCards { expected: usize, got: usize },
+    WrongBoardSize { expected: RangeInclusive<usize>, got: usize },
+    DuplicateCards(Deck),
+    UnknownCards(Deck),
+}
 
-    suit * 13 + value
+impl Display for HoleBoardError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HoleBoardError::WrongHoleCards { expected, got } => write!(f, "expected {} hole cards, got {}", expected, got),
+            HoleBoardError::WrongBoardSize { expected, got } => {
+                write!(f, "board must have {} to {} cards, got {}", expected.start(), expected.end(), got)
+            },
+            HoleBoardError::DuplicateCards(deck) => write!(f, "cards are both in the hole and on the board: {:#x}", deck),
+            HoleBoardError::UnknownCards(deck) => write!(f, "deck contains bits outside of the 52 cards: {:#x}", deck),
+        }
+    }
+}
 
-    // let value = Value::try_from(&s[0..1]).unwrap() as u8;
-    // let suit = Suit::try_from(&s[1..2]).unwrap() as u8;
-    // suit * 4 + value
+impl std::error::Error for HoleBoardError {}
+
+// Checks a player's hole cards and the board before they are evaluated together
+pub fn check_hole_and_board(hole: Deck, hole_cards: usize, board: Deck, board_sizes: RangeInclusive<usize>) -> Result<(), HoleBoardError> {
+    let unknown = (hole | board) & !FULL_DECK;
+    if unknown != 0 {
+        return Err(HoleBoardError::UnknownCards(unknown));
+    }
+    if hole.count_ones() as usize != hole_cards {
+        return Err(HoleBoardError::WrongHoleCards { expected: hole_cards, got: hole.count_ones() as usize });
+    }
+    if !board_sizes.contains(&(board.count_ones() as usize)) {
+        return Err(HoleBoardError::WrongBoardSize { expected: board_sizes, got: board.count_ones() as usize });
+    }
+    if hole & board != 0 {
+        return Err(HoleBoardError::DuplicateCards(hole & board));
+    }
+    Ok(())
 }
 
-pub fn get_random_card(deck: Deck) -> Deck {
-    let distribution = Uniform::new(0, deck.count_zeros() - 12);
-    let mut generator = rand::thread_rng();
-    let mut value = distribution.sample(&mut generator);
+fn single_char(s: &str) -> Result<char, ParseError> {
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(c),
+        _ => Err(ParseError::WrongLength(s.chars().count())),
+    }
+}
+
+pub const FULL_DECK: Deck = (1 << 52) - 1;
+
+// Set of distinct cards backed by the same bit layout as Deck
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct CardSet(Deck);
+
+impl CardSet {
+    pub const EMPTY: CardSet = CardSet(0);
+    pub const FULL: CardSet = CardSet(FULL_DECK);
+
+    pub fn from_deck(deck: Deck) -> Self {
+        assert_eq!(deck >> 52, 0, "Deck contains bits outside of the 52 cards");
+        CardSet(deck)
+    }
+
+    pub fn to_deck(self) -> Deck {
+        self.0
+    }
+
+    pub fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn contains(self, card: Card) -> bool {
+        self.0 & card.to_deck() != 0
+    }
+
+    pub fn is_disjoint(self, other: CardSet) -> bool {
+        self.0 & other.0 == 0
+    }
+
+    pub fn is_subset(self, other: CardSet) -> bool {
+        self.0 & !other.0 == 0
+    }
+
+    // Cards of the full deck that are not part of the set
+    pub fn complement(self) -> CardSet {
+        CardSet(FULL_DECK & !self.0)
+    }
+
+    // Returns whether the card was not present before
+    pub fn insert(&mut self, card: Card) -> bool {
+        let absent = !self.contains(card);
+        self.0 |= card.to_deck();
+        absent
+    }
+
+    // Returns whether the card was present before
+    pub fn remove(&mut self, card: Card) -> bool {
+        let present = self.contains(card);
+        self.0 &= !card.to_deck();
+        present
+    }
+
+    pub fn iter(self) -> CardSetIter {
+        CardSetIter(self.0)
+    }
+}
+
+impl From<Card> for CardSet {
+    fn from(card: Card) -> Self {
+        CardSet(card.to_deck())
+    }
+}
+
+impl BitOr for CardSet {
+    type Output = CardSet;
+
+    fn bitor(self, rhs: CardSet) -> CardSet {
+        CardSet(self.0 | rhs.0)
+    }
+}
+
+impl BitAnd for CardSet {
+    type Output = CardSet;
+
+    fn bitand(self, rhs: CardSet) -> CardSet {
+        CardSet(self.0 & rhs.0)
+    }
+}
+
+impl Sub for CardSet {
+    type Output = CardSet;
+
+    fn sub(self, rhs: CardSet) -> CardSet {
+        CardSet(self.0 & !rhs.0)
+    }
+}
+
+impl BitOrAssign for CardSet {
+    fn bitor_assign(&mut self, rhs: CardSet) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl BitAndAssign for CardSet {
+    fn bitand_assign(&mut self, rhs: CardSet) {
+        self.0 &= rhs.0;
+    }
+}
 
-    for i in 0..52 {
-        if deck & (0x1 << i) != 0 {
+impl SubAssign for CardSet {
+    fn sub_assign(&mut self, rhs: CardSet) {
+        self.0 &= !rhs.0;
+    }
+}
+
+impl FromIterator<Card> for CardSet {
+    fn from_iter<I: IntoIterator<Item = Card>>(iter: I) -> Self {
+        let mut set = CardSet::EMPTY;
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<Card> for CardSet {
+    fn extend<I: IntoIterator<Item = Card>>(&mut self, iter: I) {
+        for card in iter {
+            self.insert(card);
+        }
+    }
+}
+
+impl FromIterator<CardSet> for CardSet {
+    fn from_iter<I: IntoIterator<Item = CardSet>>(iter: I) -> Self {
+        iter.into_iter().fold(CardSet::EMPTY, |set, other| set | other)
+    }
+}
+
+impl IntoIterator for CardSet {
+    type Item = Card;
+    type IntoIter = CardSetIter;
+
+    fn into_iter(self) -> CardSetIter {
+        self.iter()
+    }
+}
+
+impl Display for CardSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, card) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", card)?;
+        }
+        Ok(())
+    }
+}
+
+// Yields the cards of a set in ascending bit order
+#[derive(Debug, Clone)]
+pub struct CardSetIter(Deck);
+
+impl Iterator for CardSetIter {
+    type Item = Card;
+
+    fn next(&mut self) -> Option<Card> {
+        if self.0 == 0 {
+            return None;
+        }
+        let index = self.0.trailing_zeros() as u8;
+        self.0 &= self.0 - 1;
+        Card::from_index(index)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.0.count_ones() as usize;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for CardSetIter {}
+
+// Ranks, suits and cards are stored in their usual notation such as "As",
+// card sets as a list of such cards
+#[cfg(feature = "serde")]
+mod serde_impls {
+    use super::{Card, CardSet, Rank, Suit};
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    macro_rules! string_serde {
+        ($type:ty) => {
+            impl Serialize for $type {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.collect_str(self)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $type {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    String::deserialize(deserializer)?.parse().map_err(D::Error::custom)
+                }
+            }
+        };
+    }
+
+    string_serde!(Rank);
+    string_serde!(Suit);
+    string_serde!(Card);
+
+    impl Serialize for CardSet {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_seq(self.iter())
+        }
+    }
+
+    impl<'de> Deserialize<'de> for CardSet {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let cards = Vec::<Card>::deserialize(deserializer)?;
+            let set: CardSet = cards.iter().copied().collect();
+            match set.len() == cards.len() {
+                true => Ok(set),
+                false => Err(D::Error::custom("duplicate card in card set")),
+            }
+        }
+    }
+}
+
+// Strict only accepts the canonical "As" form, Lenient also accepts lowercase ranks,
+// "10" for tens and suit symbols such as "A♠"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Notation {
+    #[default]
+    Strict,
+    Lenient,
+}
+
+// Emoji presentation selector that chat clients like to append to suit symbols
+const VARIATION_SELECTOR: char = '\u{fe0f}';
+
+fn parse_rank(chars: &[char], notation: Notation) -> Result<(Rank, usize), ParseError> {
+    match (chars, notation) {
+        (['1', '0', ..], Notation::Lenient) => Ok((Rank::Ten, 2)),
+        ([c, ..], Notation::Lenient) => Rank::from_char(c.to_ascii_uppercase()).map(|rank| (rank, 1)).ok_or(ParseError::UnknownRank(*c)),
+        ([c, ..], Notation::Strict) => Rank::from_char(*c).map(|rank| (rank, 1)).ok_or(ParseError::UnknownRank(*c)),
+        ([], _) => Err(ParseError::WrongLength(0)),
+    }
+}
+
+fn parse_suit(c: char, notation: Notation) -> Result<Suit, ParseError> {
+    match notation {
+        Notation::Strict => Suit::from_char(c),
+        Notation::Lenient => Suit::from_char(c).or_else(|| Suit::from_symbol(c)),
+    }.ok_or(ParseError::UnknownSuit(c))
+}
+
+fn is_separator(c: char, notation: Notation) -> bool {
+    match notation {
+        Notation::Strict => c.is_whitespace() || c == ',',
+        Notation::Lenient => c.is_whitespace() || ",;[]()".contains(c) || c == VARIATION_SELECTOR,
+    }
+}
+
+pub fn parse_card(s: &str, notation: Notation) -> Result<Card, ParseError> {
+    let chars: Vec<char> = match notation {
+        Notation::Strict => s.chars().collect(),
+        Notation::Lenient => s.trim().chars().filter(|c| *c != VARIATION_SELECTOR).collect(),
+    };
+    let (rank, len) = parse_rank(&chars, notation)?;
+    match chars[len..] {
+        [suit] => Ok(Card::new(rank, parse_suit(suit, notation)?)),
+        _ => Err(ParseError::WrongLength(chars.len())),
+    }
+}
+
+// Splits a string of cards such as "AsKd7c" or "As, Kd 7c" into a deck,
+// errors report the zero-based position of the offending card
+pub fn parse_cards(s: &str, notation: Notation) -> Result<Deck, ParseError> {
+    let chars: Vec<char> = s.chars().collect();
+    let mut deck: Deck = 0;
+    let mut position = 0;
+    let mut i = 0;
+
+    while i < chars.len() {
+        if is_separator(chars[i], notation) {
+            i += 1;
             continue;
         }
-        if value == 0 {
-            return 0x1 << i;
+
+        let bad_token = |error| ParseError::BadToken { position, error: Box::new(error) };
+        let (rank, len) = parse_rank(&chars[i..], notation).map_err(bad_token)?;
+        let suit = match chars.get(i + len) {
+            Some(c) if !is_separator(*c, notation) => parse_suit(*c, notation).map_err(bad_token)?,
+            _ => return Err(bad_token(ParseError::WrongLength(len))),
+        };
+        let card = Card::new(rank, suit);
+        if deck & card.to_deck() != 0 {
+            return Err(bad_token(ParseError::DuplicateCard(card)));
         }
-        value -= 1;
+        deck |= card.to_deck();
+        position += 1;
+        i += len + 1;
     }
 
-    panic!();
+    Ok(deck)
+}
+
+pub fn to_deck<T: AsRef<str>>(cards: &[T]) -> Result<Deck, ParseError>
+{
+    let mut deck: Deck = 0;
+    for (position, card) in cards.iter().enumerate() {
+        let bad_token = |error| ParseError::BadToken { position, error: Box::new(error) };
+        let card: Card = card.as_ref().parse().map_err(bad_token)?;
+        if deck & card.to_deck() != 0 {
+            return Err(bad_token(ParseError::DuplicateCard(card)));
+        }
+        deck |= card.to_deck();
+    }
+    Ok(deck)
+}
+
+// Deck of cards known to be valid, for tests
+#[cfg(test)]
+pub(crate) fn cards(cards: &[&str]) -> Deck {
+    to_deck(cards).unwrap()
+}
+
+pub fn from_deck(deck: Deck) -> Vec<String> {
+    CardSet::from_deck(deck).iter().map(|card| card.to_string()).collect()
+}
+
+pub fn card_to_string(value: u8) -> String {
+    Card::from_index(value).unwrap().to_string()
+}
+
+pub fn card_from_string(s: &str) -> Result<u8, ParseError> {
+    Ok(s.parse::<Card>()?.index())
+}
+
+// StdRng is reproducible for a given seed as long as the rand version stays the same
+pub fn seeded_rng(seed: u64) -> StdRng {
+    StdRng::seed_from_u64(seed)
+}
+
+// Picks one of the cards missing from deck uniformly, None if every card has been dealt
+pub fn random_card<R: Rng + ?Sized>(deck: Deck, rng: &mut R) -> Option<Card> {
+    let remaining = CardSet::from_deck(deck).complement();
+    if remaining.is_empty() {
+        return None;
+    }
+    remaining.iter().nth(rng.gen_range(0..remaining.len()))
+}
+
+// Returns the cards missing from deck in random order
+pub fn shuffle_remaining<R: Rng + ?Sized>(deck: Deck, rng: &mut R) -> Vec<Card> {
+    let mut cards: Vec<Card> = CardSet::from_deck(deck).complement().iter().collect();
+    cards.shuffle(rng);
+    cards
+}
+
+// Draws count random cards missing from deck, None if there are not enough of them
+pub fn deal_cards<R: Rng + ?Sized>(deck: Deck, count: usize, rng: &mut R) -> Option<CardSet> {
+    let mut cards: Vec<Card> = CardSet::from_deck(deck).complement().iter().collect();
+    if cards.len() < count {
+        return None;
+    }
+    Some(cards.partial_shuffle(rng, count).0.iter().copied().collect())
+}
+
+pub fn get_random_card(deck: Deck) -> Deck {
+    random_card(deck, &mut rand::thread_rng()).expect("No cards left to deal").to_deck()
 }
 
 #[cfg(test)]
@@ -115,18 +671,164 @@ mod test {
     fn test_card_string_conversions() {
         (0..52).for_each(|val| {
             let s = super::card_to_string(val);
-            assert_eq!(super::card_from_string(&s), val);
+            assert_eq!(super::card_from_string(&s), Ok(val));
         });
     }
 
+    #[test]
+    fn test_card_type_conversions() {
+        let card: Card = "Td".parse().unwrap();
+        assert_eq!(card, Card::new(Rank::Ten, Suit::Diamonds));
+        assert_eq!(card.index(), 3 * 13 + 8);
+        assert_eq!(Card::from_index(card.index()), Some(card));
+        assert_eq!(card.to_string(), "Td");
+        assert_eq!(Card::from_index(52), None);
+    }
+
+    #[test]
+    fn test_card_parse_errors() {
+        assert_eq!("Tx".parse::<Card>(), Err(ParseError::UnknownSuit('x')));
+        assert_eq!("1c".parse::<Card>(), Err(ParseError::UnknownRank('1')));
+        assert_eq!("Tcc".parse::<Card>(), Err(ParseError::WrongLength(3)));
+        assert_eq!("T".parse::<Card>(), Err(ParseError::WrongLength(1)));
+        assert_eq!("A♠".parse::<Card>(), Err(ParseError::UnknownSuit('♠')));
+        assert_eq!("♠".parse::<Rank>(), Err(ParseError::UnknownRank('♠')));
+        assert_eq!("".parse::<Suit>(), Err(ParseError::WrongLength(0)));
+    }
+
+    #[test]
+    fn test_deck_parse_errors() {
+        assert_eq!(
+            to_deck(&["As", "Kd", "As"]),
+            Err(ParseError::BadToken {
+                position: 2,
+                error: Box::new(ParseError::DuplicateCard(Card::new(Rank::Ace, Suit::Spades))),
+            })
+        );
+        let error = to_deck(&["As", "Kx"]).unwrap_err();
+        assert_eq!(error, ParseError::BadToken { position: 1, error: Box::new(ParseError::UnknownSuit('x')) });
+        assert_eq!(error.to_string(), "card 2: unknown suit 'x'");
+    }
+
+    #[test]
+    fn test_lenient_card_notation() {
+        let ten_of_hearts = Card::new(Rank::Ten, Suit::Hearts);
+        assert_eq!(parse_card("10h", Notation::Lenient), Ok(ten_of_hearts));
+        assert_eq!(parse_card("th", Notation::Lenient), Ok(ten_of_hearts));
+        assert_eq!(parse_card("T♥", Notation::Lenient), Ok(ten_of_hearts));
+        assert_eq!(parse_card("t♡\u{fe0f}", Notation::Lenient), Ok(ten_of_hearts));
+        assert_eq!(parse_card("ad", Notation::Lenient), Ok(Card::new(Rank::Ace, Suit::Diamonds)));
+        assert_eq!(parse_card("A♠", Notation::Lenient), Ok(Card::new(Rank::Ace, Suit::Spades)));
+        assert_eq!(parse_card("10", Notation::Lenient), Err(ParseError::WrongLength(2)));
+        assert_eq!(parse_card("1h", Notation::Lenient), Err(ParseError::UnknownRank('1')));
+    }
+
+    #[test]
+    fn test_strict_card_notation() {
+        assert_eq!(parse_card("Th", Notation::Strict), Ok(Card::new(Rank::Ten, Suit::Hearts)));
+        assert_eq!(parse_card("10h", Notation::Strict), Err(ParseError::UnknownRank('1')));
+        assert_eq!(parse_card("ad", Notation::Strict), Err(ParseError::UnknownRank('a')));
+        assert_eq!(parse_card("A♠", Notation::Strict), Err(ParseError::UnknownSuit('♠')));
+    }
+
+    #[test]
+    fn test_concatenated_cards() {
+        let expected = to_deck(&["As", "Kd", "7c"]).unwrap();
+        assert_eq!(parse_cards("AsKd7c", Notation::Strict), Ok(expected));
+        assert_eq!(parse_cards("As, Kd 7c", Notation::Strict), Ok(expected));
+        assert_eq!(parse_cards("[a♠ k♦ 7♣]", Notation::Lenient), Ok(expected));
+        assert_eq!(parse_cards("10hJh", Notation::Lenient), Ok(to_deck(&["Th", "Jh"]).unwrap()));
+        assert_eq!(parse_cards("", Notation::Strict), Ok(0));
+        assert_eq!(
+            parse_cards("AsKd7", Notation::Strict),
+            Err(ParseError::BadToken { position: 2, error: Box::new(ParseError::WrongLength(1)) })
+        );
+        assert_eq!(
+            parse_cards("AsK 7c", Notation::Strict),
+            Err(ParseError::BadToken { position: 1, error: Box::new(ParseError::WrongLength(1)) })
+        );
+        assert_eq!(
+            parse_cards("AsKdas", Notation::Lenient),
+            Err(ParseError::BadToken {
+                position: 2,
+                error: Box::new(ParseError::DuplicateCard(Card::new(Rank::Ace, Suit::Spades))),
+            })
+        );
+    }
+
     #[test]
     fn test_deck_conversions() {
         let d = 0xfffffffffffff as Deck;
         let c = from_deck(d);
-        let d2 = to_deck(&c);
+        let d2 = to_deck(&c).unwrap();
         assert_eq!(d, d2);
     }
 
+    #[test]
+    fn test_card_set_algebra() {
+        let a = CardSet::from_deck(to_deck(&["As", "Kd", "7c"]).unwrap());
+        let b = CardSet::from_deck(to_deck(&["Kd", "2h"]).unwrap());
+        assert_eq!((a | b).len(), 4);
+        assert_eq!((a & b).to_deck(), to_deck(&["Kd"]).unwrap());
+        assert_eq!((a - b).to_string(), "7c As");
+        assert_eq!(a.complement().len(), 49);
+        assert!(a.is_disjoint(a.complement()));
+        assert!((a & b).is_subset(a));
+
+        let mut set = CardSet::EMPTY;
+        let ace: Card = "As".parse().unwrap();
+        assert!(set.insert(ace));
+        assert!(!set.insert(ace));
+        assert!(set.contains(ace));
+        assert!(set.remove(ace));
+        assert!(!set.remove(ace));
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn test_card_set_iteration() {
+        assert_eq!(CardSet::FULL.iter().len(), 52);
+        let cards: Vec<u8> = CardSet::FULL.iter().map(|card| card.index()).collect();
+        assert_eq!(cards, (0..52).collect::<Vec<u8>>());
+        let set: CardSet = ["Qh", "3d"].iter().map(|s| s.parse::<Card>().unwrap()).collect();
+        assert_eq!(set.to_deck(), to_deck(&["Qh", "3d"]).unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "Deck contains bits outside of the 52 cards")]
+    fn test_card_set_invariant() {
+        CardSet::from_deck(1 << 52);
+    }
+
+    #[test]
+    fn test_hole_and_board_checks() {
+        let board = cards(&["Th", "Jh", "Qd"]);
+        assert_eq!(check_hole_and_board(cards(&["Ah", "Kh"]), 2, board, 3..=5), Ok(()));
+        assert_eq!(check_hole_and_board(cards(&["Ah"]), 2, board, 3..=5), Err(HoleBoardError::WrongHoleCards { expected: 2, got: 1 }));
+        assert_eq!(check_hole_and_board(cards(&["Ah", "Kh"]), 2, board, 4..=4), Err(HoleBoardError::WrongBoardSize { expected: 4..=4, got: 3 }));
+        assert_eq!(check_hole_and_board(cards(&["Ah", "Jh"]), 2, board, 3..=5), Err(HoleBoardError::DuplicateCards(cards(&["Jh"]))));
+        assert_eq!(check_hole_and_board(cards(&["Ah", "Kh"]) | 1 << 55, 2, board, 3..=5), Err(HoleBoardError::UnknownCards(1 << 55)));
+        let error = HoleBoardError::WrongBoardSize { expected: 3..=5, got: 2 };
+        assert_eq!(error.to_string(), "board must have 3 to 5 cards, got 2");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let card: Card = "Td".parse().unwrap();
+        assert_eq!(serde_json::to_string(&card).unwrap(), "\"Td\"");
+        assert_eq!(serde_json::from_str::<Card>("\"Td\"").unwrap(), card);
+        assert!(serde_json::from_str::<Card>("\"Tx\"").is_err());
+        assert_eq!(serde_json::to_string(&Rank::Ace).unwrap(), "\"A\"");
+        assert_eq!(serde_json::from_str::<Suit>("\"h\"").unwrap(), Suit::Hearts);
+
+        let set = CardSet::from_deck(to_deck(&["As", "Kd", "7c"]).unwrap());
+        let json = serde_json::to_string(&set).unwrap();
+        assert_eq!(json, "[\"7c\",\"As\",\"Kd\"]");
+        assert_eq!(serde_json::from_str::<CardSet>(&json).unwrap(), set);
+        assert!(serde_json::from_str::<CardSet>("[\"As\",\"As\"]").is_err());
+    }
+
     #[test]
     fn test_random_deck_generation() {
         let mut deck = 0;
@@ -135,4 +837,26 @@ mod test {
         });
         assert_eq!(deck, 0xfffffffffffff);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_seeded_dealing_is_reproducible() {
+        let dealt = to_deck(&["As", "Ks"]).unwrap();
+        let first = deal_cards(dealt, 5, &mut seeded_rng(42)).unwrap();
+        let second = deal_cards(dealt, 5, &mut seeded_rng(42)).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(first.len(), 5);
+        assert!(first.is_disjoint(CardSet::from_deck(dealt)));
+
+        assert_eq!(shuffle_remaining(dealt, &mut seeded_rng(7)), shuffle_remaining(dealt, &mut seeded_rng(7)));
+        assert_eq!(random_card(dealt, &mut seeded_rng(1)), random_card(dealt, &mut seeded_rng(1)));
+    }
+
+    #[test]
+    fn test_dealing_from_exhausted_deck() {
+        let mut rng = seeded_rng(0);
+        assert_eq!(random_card(FULL_DECK, &mut rng), None);
+        assert_eq!(deal_cards(FULL_DECK - 1, 2, &mut rng), None);
+        assert_eq!(deal_cards(FULL_DECK - 1, 1, &mut rng), Some(CardSet::from_deck(1)));
+        assert_eq!(shuffle_remaining(0, &mut rng).len(), 52);
+    }
+}
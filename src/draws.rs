@@ -0,0 +1,221 @@
+use crate::analyser::{self, merge_suits, split_suits};
+use crate::deck::{check_hole_and_board, CardSet, Deck, HoleBoardError, Suit};
+use crate::hand::{Category, Hand};
+use crate::variant::Variant;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Draw {
+    // Four cards of the suit, at least one of them in the hole
+    FlushDraw(Suit),
+    // Two ranks complete a straight. A double gutshot has the same eight outs and counts as one.
+    OpenEnded,
+    // A single rank completes a straight
+    Gutshot,
+    // Three cards of the suit on the flop, so turn and river both have to match
+    BackdoorFlush(Suit),
+    // Number of hole cards above every board card while the hand is no more than a high card
+    Overcards(u8),
+}
+
+impl Display for Draw {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Draw::FlushDraw(suit) => write!(f, "flush draw in {}", suit.name()),
+            Draw::OpenEnded => write!(f, "open-ended straight draw"),
+            Draw::Gutshot => write!(f, "gutshot"),
+            Draw::BackdoorFlush(suit) => write!(f, "backdoor flush draw in {}", suit.name()),
+            Draw::Overcards(1) => write!(f, "one overcard"),
+            Draw::Overcards(count) => write!(f, "{} overcards", count),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outs {
+    // The hand as it stands
+    pub hand: Hand,
+    // Unseen cards that lift the hand into a better category, by that category.
+    // A card only counts when the board does not make the same category on its own.
+    pub by_category: BTreeMap<Category, CardSet>,
+    pub draws: Vec<Draw>,
+}
+
+impl Outs {
+    pub fn of(&self, category: Category) -> CardSet {
+        self.by_category.get(&category).copied().unwrap_or_default()
+    }
+
+    pub fn all(&self) -> CardSet {
+        self.by_category.values().copied().collect()
+    }
+
+    pub fn count(&self) -> usize {
+        self.all().len()
+    }
+}
+
+// "9 outs to Flush, 6 outs to One Pair" from the strongest category down
+impl Display for Outs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.by_category.is_empty() {
+            return write!(f, "no outs");
+        }
+        let parts: Vec<String> = self.by_category
+            .iter()
+            .rev()
+            .map(|(category, cards)| match cards.len() {
+                1 => format!("1 out to {}", category),
+                count => format!("{} outs to {}", count, category),
+            })
+            .collect();
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+fn has_straight(ranks: u16) -> bool {
+    let (wheel, _) = Variant::Holdem.wheel();
+    ranks & wheel == wheel || (0..9).any(|i| ranks >> i & 0x1f == 0x1f)
+}
+
+// Category the cards make when only pairs and the like count, for boards too small for a hand
+fn paired_category(deck: Deck) -> Category {
+    let suits = split_suits(deck);
+    let mut counts: Vec<u32> = (0..13).map(|r| suits.iter().filter(|suit| *suit & (1 << r) != 0).count() as u32).collect();
+    counts.sort_unstable_by(|a, b| b.cmp(a));
+    match counts[..2] {
+        [4, _] => Category::Quads,
+        [3, 2] | [3, 3] => Category::FullHouse,
+        [3, _] => Category::Set,
+        [2, 2] => Category::TwoPairs,
+        [2, _] => Category::Pair,
+        _ => Category::HighCard,
+    }
+}
+
+fn board_category(board: Deck) -> Category {
+    match board.count_ones() {
+        5.. => analyser::analyse(board).category(),
+        _ => paired_category(board),
+    }
+}
+
+// Unseen cards that improve a Hold'em hand on the flop or the turn, and the draws it has.
+// Outs only exist while cards are to come, so the board has 3 or 4 cards.
+pub fn outs(hole: Deck, board: Deck) -> Result<Outs, HoleBoardError> {
+    check_hole_and_board(hole, 2, board, 3..=4)?;
+
+    let cards = hole | board;
+    let hand = analyser::analyse(cards);
+    let mut by_category: BTreeMap<Category, CardSet> = BTreeMap::new();
+    for card in CardSet::from_deck(cards).complement().iter() {
+        let category = analyser::analyse(cards | card.to_deck()).category();
+        if category > hand.category() && category > board_category(board | card.to_deck()) {
+            by_category.entry(category).or_default().insert(card);
+        }
+    }
+
+    let draws = find_draws(hole, board, &hand);
+    Ok(Outs { hand, by_category, draws })
+}
+
+fn find_draws(hole: Deck, board: Deck, hand: &Hand) -> Vec<Draw> {
+    let cards = hole | board;
+    let mut draws = vec![];
+
+    if hand.category() < Category::Flush {
+        for suit in Suit::ALL {
+            let mask = 0x1fff << (13 * suit.index() as u32);
+            let in_hole = (hole & mask).count_ones();
+            match (cards & mask).count_ones() {
+                4 if in_hole > 0 => draws.push(Draw::FlushDraw(suit)),
+                3 if in_hole > 0 && board.count_ones() == 3 => draws.push(Draw::BackdoorFlush(suit)),
+                _ => {},
+            }
+        }
+    }
+
+    let ranks = |deck: Deck| merge_suits(&split_suits(deck));
+
+    // Ranks that make a straight which the board does not make by itself
+    if !has_straight(ranks(cards)) {
+        let completing = (0..13)
+            .map(|r| 1 << r)
+            .filter(|rank| ranks(cards) & rank == 0)
+            .filter(|rank| has_straight(ranks(cards) | rank) && !has_straight(ranks(board) | rank))
+            .count();
+        match completing {
+            0 => {},
+            1 => draws.push(Draw::Gutshot),
+            _ => draws.push(Draw::OpenEnded),
+        }
+    }
+
+    if hand.category() == Category::HighCard {
+        let top = ranks(board).ilog2();
+        let overcards = (0..13).filter(|r| *r > top && ranks(hole) & (1 << r) != 0).count() as u8;
+        if overcards > 0 {
+            draws.push(Draw::Overcards(overcards));
+        }
+    }
+
+    draws.sort_unstable();
+    draws
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::deck::cards;
+
+    #[test]
+    fn test_flush_draw_with_overcards() {
+        let outs = outs(cards(&["Ah", "Kh"]), cards(&["Qh", "7h", "2c"])).unwrap();
+        assert_eq!(outs.of(Category::Flush).len(), 9);
+        assert_eq!(outs.of(Category::Pair).len(), 6);
+        assert_eq!(outs.draws, vec![Draw::FlushDraw(Suit::Hearts), Draw::Overcards(2)]);
+        assert_eq!(outs.to_string(), "9 outs to Flush, 6 outs to One Pair");
+        assert_eq!(outs.count(), 15);
+    }
+
+    #[test]
+    fn test_straight_draws() {
+        let open_ended = outs(cards(&["9c", "8d"]), cards(&["7h", "6s", "2c"])).unwrap();
+        assert!(open_ended.draws.contains(&Draw::OpenEnded));
+        assert_eq!(open_ended.of(Category::Straight).len(), 8);
+
+        let gutshot = outs(cards(&["9c", "8d"]), cards(&["6h", "5s", "Kc"])).unwrap();
+        assert!(gutshot.draws.contains(&Draw::Gutshot));
+        assert_eq!(gutshot.of(Category::Straight).len(), 4);
+
+        // The board makes the straight by itself with any nine or four
+        let board_draw = outs(cards(&["Ac", "Ad"]), cards(&["8h", "7s", "6c", "5d"])).unwrap();
+        assert!(board_draw.draws.is_empty());
+        assert!(board_draw.of(Category::Straight).is_empty());
+    }
+
+    #[test]
+    fn test_backdoor_flush() {
+        let outs = outs(cards(&["Ah", "Td"]), cards(&["Kh", "7h", "2c"])).unwrap();
+        assert!(outs.draws.contains(&Draw::BackdoorFlush(Suit::Hearts)));
+        assert!(outs.of(Category::Flush).is_empty());
+        assert_eq!(outs.draws.iter().filter(|draw| matches!(draw, Draw::Overcards(_))).count(), 1);
+        assert_eq!(Draw::Overcards(1).to_string(), "one overcard");
+    }
+
+    #[test]
+    fn test_board_pairs_do_not_count() {
+        // A second king pairs the board for everyone, only the aces and queens help
+        let outs = outs(cards(&["Ac", "Qd"]), cards(&["Kh", "7s", "2c", "9d"])).unwrap();
+        assert_eq!(outs.hand.category(), Category::HighCard);
+        assert_eq!(outs.of(Category::Pair).len(), 6);
+        assert_eq!(outs.of(Category::Straight).len(), 0);
+    }
+
+    #[test]
+    fn test_no_outs_on_the_river() {
+        let error = HoleBoardError::WrongBoardSize { expected: 3..=4, got: 5 };
+        assert_eq!(outs(cards(&["Ac", "Qd"]), cards(&["Kh", "7s", "2c", "9d", "4s"])), Err(error));
+    }
+}
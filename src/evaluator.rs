@@ -0,0 +1,144 @@
+use crate::analyser;
+use crate::deck::{self, Deck};
+use crate::hand::Hand;
+use crate::variant::Variant;
+use rand::Rng;
+use std::cmp::Ordering;
+
+// Turns a deck into a strength, where a stronger hand compares greater.
+// Anything that does this for the cards of a player can drive the predictor.
+pub trait Evaluator {
+    type Strength: Ord;
+
+    fn evaluate(&self, deck: Deck) -> Self::Strength;
+
+    // Appends the strengths of all decks, backends that profit from batching override this
+    fn evaluate_many(&self, decks: &[Deck], strengths: &mut Vec<Self::Strength>) {
+        strengths.extend(decks.iter().map(|deck| self.evaluate(*deck)));
+    }
+}
+
+// Plain functions and closures are evaluators as well
+impl<F, S> Evaluator for F
+where
+    F: Fn(Deck) -> S,
+    S: Ord,
+{
+    type Strength = S;
+
+    fn evaluate(&self, deck: Deck) -> S {
+        self(deck)
+    }
+}
+
+// The bit-twiddling analyser that works out the hand from the suit masks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Analyser {
+    variant: Variant,
+}
+
+impl Analyser {
+    pub fn new(variant: Variant) -> Self {
+        Analyser { variant }
+    }
+
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+}
+
+impl Evaluator for Analyser {
+    type Strength = VariantHand;
+
+    fn evaluate(&self, deck: Deck) -> VariantHand {
+        VariantHand {
+            variant: self.variant,
+            hand: analyser::analyse_variant(deck, self.variant),
+        }
+    }
+}
+
+// A hand that compares by the category order of its variant
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VariantHand {
+    pub variant: Variant,
+    pub hand: Hand,
+}
+
+impl Ord for VariantHand {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.variant.compare(&self.hand, &other.hand)
+    }
+}
+
+impl PartialOrd for VariantHand {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// First deck on which two evaluators disagree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch<S> {
+    pub deck: Deck,
+    pub left: S,
+    pub right: S,
+}
+
+// Evaluates every deck with both evaluators and stops at the first different result.
+// Returns the number of decks that were checked.
+pub fn cross_check<A, B, I>(left: &A, right: &B, decks: I) -> Result<u64, Mismatch<A::Strength>>
+where
+    A: Evaluator,
+    B: Evaluator<Strength = A::Strength>,
+    I: IntoIterator<Item = Deck>,
+{
+    let mut checked = 0;
+    for deck in decks {
+        let (l, r) = (left.evaluate(deck), right.evaluate(deck));
+        if l != r {
+            return Err(Mismatch { deck, left: l, right: r });
+        }
+        checked += 1;
+    }
+    Ok(checked)
+}
+
+// Random decks of size cards drawn from the full deck, to feed cross_check
+pub fn random_decks<R: Rng + ?Sized>(rng: &mut R, size: usize, count: usize) -> impl Iterator<Item = Deck> + '_ {
+    (0..count).map(move |_| deck::deal_cards(0, size, rng).unwrap().to_deck())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::combinations::Combinations;
+    use crate::deck::{to_deck, FULL_DECK};
+    use crate::hand::Category;
+
+    #[test]
+    fn test_analyser_matches_analyse() {
+        let analyser = |deck| Analyser::default().evaluate(deck).hand;
+        let checked = cross_check(&analyser, &analyser::analyse, random_decks(&mut deck::seeded_rng(1), 7, 10000));
+        assert_eq!(checked, Ok(10000));
+    }
+
+    #[test]
+    fn test_cross_check_reports_mismatch() {
+        let category = |deck| analyser::analyse(deck).category();
+        let error = cross_check(&category, &|_| Category::HighCard, Combinations::new(FULL_DECK, 5)).unwrap_err();
+        assert_eq!(error, Mismatch { deck: 0b11111, left: Category::StraightFlush, right: Category::HighCard });
+
+        let pair = to_deck(&["2c", "2h", "5c", "7d", "As"]).unwrap();
+        assert_eq!(cross_check(&category, &|_| Category::Pair, [pair]), Ok(1));
+    }
+
+    #[test]
+    fn test_variant_order() {
+        let flush = to_deck(&["Ah", "Jh", "9h", "8h", "6h"]).unwrap();
+        let fullhouse = to_deck(&["6h", "6s", "6d", "7c", "7s"]).unwrap();
+        let short_deck = Analyser::new(Variant::ShortDeck);
+        assert!(short_deck.evaluate(flush) > short_deck.evaluate(fullhouse));
+        assert!(Analyser::default().evaluate(flush) < Analyser::default().evaluate(fullhouse));
+    }
+}
@@ -1,8 +1,16 @@
-use crate::deck::card_to_string;
+use crate::combinations::binomial;
+use crate::deck::{Card, Rank, Suit};
+use crate::variant::Variant;
 use std::cmp::Ordering;
 use std::fmt::Display;
-
-#[derive(Debug, PartialEq, PartialOrd)]
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+// Hands are totally ordered the way they rank at showdown: first by category and then by
+// the ranks that make up the hand from the most to the least significant one.
+// Suits never matter, so hands that compare equal split the pot.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(tag = "category", content = "value", rename_all = "snake_case", try_from = "UncheckedHand"))]
 pub enum Hand {
     HighCard(HighCard),
     Pairs(Pairs),
@@ -14,213 +22,882 @@ pub enum Hand {
     StraightFlush(StraightFlush),
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Category {
+    HighCard,
+    Pair,
+    TwoPairs,
+    Set,
+    Straight,
+    Flush,
+    FullHouse,
+    Quads,
+    StraightFlush,
+}
+
 impl Hand {
-    pub fn highcard(cards: &[u8]) -> Self {
+    // The five ranks of the hand with repetitions, from the most to the least significant.
+    // Straights run down from their top card, so the wheel ends with the ace.
+    pub fn ranks(&self) -> [Rank; 5] {
+        self.ranks_variant(Variant::Holdem)
+    }
+
+    // Same as ranks, with the wheel of the variant: A-6-7-8-9 in short deck
+    pub fn ranks_variant(&self, variant: Variant) -> [Rank; 5] {
+        match self {
+            Hand::HighCard(cards) => *cards,
+            Hand::Pairs(Pairs{pairs, kickers}) => {
+                let mut ranks = vec![];
+                for pair in pairs {
+                    ranks.extend([*pair, *pair]);
+                }
+                ranks.extend(kickers);
+                ranks.try_into().unwrap()
+            },
+            Hand::Set(Set{set, kickers}) => [*set, *set, *set, kickers[0], kickers[1]],
+            Hand::Straight(top) | Hand::StraightFlush(StraightFlush{top, ..}) => {
+                let bottom = straight_bottom(*top, variant);
+                [0, 1, 2, 3, 4].map(|i| match i {
+                    4 => bottom,
+                    _ => Rank::from_index(top.index() - i).unwrap(),
+                })
+            },
+            Hand::Flush(Flush{cards, ..}) => *cards,
+            Hand::FullHouse(FullHouse{set, pair}) => [*set, *set, *set, *pair, *pair],
+            Hand::Quads(Quads{quads, kicker}) => [*quads, *quads, *quads, *quads, *kicker],
+        }
+    }
+
+    // Only flushes and straight flushes are tied to a suit
+    pub fn suit(&self) -> Option<Suit> {
+        match self {
+            Hand::Flush(Flush{suit, ..}) | Hand::StraightFlush(StraightFlush{suit, ..}) => Some(*suit),
+            _ => None,
+        }
+    }
+
+    pub fn category(&self) -> Category {
+        match self {
+            Hand::HighCard(_) => Category::HighCard,
+            Hand::Pairs(Pairs{pairs, ..}) if pairs.len() == 1 => Category::Pair,
+            Hand::Pairs(_) => Category::TwoPairs,
+            Hand::Set(_) => Category::Set,
+            Hand::Straight(_) => Category::Straight,
+            Hand::Flush(_) => Category::Flush,
+            Hand::FullHouse(_) => Category::FullHouse,
+            Hand::Quads(_) => Category::Quads,
+            Hand::StraightFlush(_) => Category::StraightFlush,
+        }
+    }
+
+    pub fn highcard(cards: &[Rank]) -> Self {
         assert!(cards.len() == 5);
         Hand::HighCard(cards.try_into().unwrap())
     }
 
-    pub fn pairs(pairs: &[u8], kickers: &[u8]) -> Self {
+    pub fn pairs(pairs: &[Rank], kickers: &[Rank]) -> Self {
         assert!(pairs.len() == 1 || pairs.len() == 2);
         assert!(pairs.len() * 2 + kickers.len() == 5);
-        Hand::Pairs(Pairs{pairs: pairs.as_ref().to_vec() , kickers: kickers.as_ref().to_vec()})
+        Hand::Pairs(Pairs{pairs: pairs.to_vec(), kickers: kickers.to_vec()})
     }
 
-    pub fn set(set: u8, kickers: &[u8]) -> Self {
+    pub fn set(set: Rank, kickers: &[Rank]) -> Self {
         assert!(kickers.len() == 2);
-        Hand::Set(Set{set: set, kickers: kickers.try_into().unwrap()})
+        Hand::Set(Set{set, kickers: kickers.try_into().unwrap()})
     }
 
-    pub fn straight(top: u8) -> Self {
+    pub fn straight(top: Rank) -> Self {
         Hand::Straight(top)
     }
 
-    pub fn flush(cards: &[u8], suit: u8) -> Self {
+    pub fn flush(cards: &[Rank], suit: Suit) -> Self {
         assert!(cards.len() == 5);
-        Hand::Flush(Flush{cards: cards.try_into().unwrap(), suit: suit})
+        Hand::Flush(Flush{cards: cards.try_into().unwrap(), suit})
     }
 
-    pub fn fullhouse(set: u8, pair: u8) -> Self {
+    pub fn fullhouse(set: Rank, pair: Rank) -> Self {
         Hand::FullHouse(FullHouse{set, pair})
     }
 
-    pub fn quads(quads: u8, kicker: u8) -> Self {
+    pub fn quads(quads: Rank, kicker: Rank) -> Self {
         Hand::Quads(Quads{quads, kicker})
     }
 
-    pub fn straightflush(top: u8, suit: u8) -> Self {
+    pub fn straightflush(top: Rank, suit: Suit) -> Self {
         Hand::StraightFlush(StraightFlush{top, suit})
     }
 }
 
-impl Display for Hand {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+// Lowest card of the straight ending in top, the wheel of the variant starts with an ace
+fn straight_bottom(top: Rank, variant: Variant) -> Rank {
+    match variant.wheel() {
+        (_, wheel_top) if wheel_top == top => Rank::Ace,
+        _ => Rank::from_index(top.index() - 4).unwrap(),
+    }
+}
 
-        fn cts<T>(val: T) -> String
-            where T: TryInto<i32>,
-            <T as TryInto<i32>>::Error: std::fmt::Debug
-        {
-            let int: i32 = val.try_into().unwrap();
-            if int < 0i32 {
-                return "A".to_string();
-            }
-            card_to_string(int as u8)[0..1].to_string()
+// Short is the terse form used by Display, Long reads like a dealer calling the hand
+// and Machine is a stable "category:ranks[:suit]" form for logs and other programs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HandFormat {
+    #[default]
+    Short,
+    Long,
+    Machine,
+}
+
+pub struct FormattedHand<'a> {
+    hand: &'a Hand,
+    format: HandFormat,
+    variant: Variant,
+}
+
+impl Hand {
+    pub fn format(&self, format: HandFormat) -> FormattedHand<'_> {
+        self.format_variant(format, Variant::Holdem)
+    }
+
+    // Straights are written with the wheel of the variant
+    pub fn format_variant(&self, format: HandFormat, variant: Variant) -> FormattedHand<'_> {
+        FormattedHand { hand: self, format, variant }
+    }
+}
+
+impl Category {
+    pub fn name(self) -> &'static str {
+        match self {
+            Category::HighCard => "High Card",
+            Category::Pair => "One Pair",
+            Category::TwoPairs => "Two Pair",
+            Category::Set => "Three of a Kind",
+            Category::Straight => "Straight",
+            Category::Flush => "Flush",
+            Category::FullHouse => "Full House",
+            Category::Quads => "Four of a Kind",
+            Category::StraightFlush => "Straight Flush",
         }
+    }
 
+    pub fn key(self) -> &'static str {
         match self {
-            Hand::HighCard(cards) => {
-                write!(f, "highcard (")?;
-                for c in cards {
-                    write!(f, "{},", cts(*c))?;
-                }
-                write!(f, "{})", 8u8 as char)   // backspace
-            },
+            Category::HighCard => "high_card",
+            Category::Pair => "pair",
+            Category::TwoPairs => "two_pairs",
+            Category::Set => "set",
+            Category::Straight => "straight",
+            Category::Flush => "flush",
+            Category::FullHouse => "full_house",
+            Category::Quads => "quads",
+            Category::StraightFlush => "straight_flush",
+        }
+    }
+}
+
+impl Display for Category {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+fn article(rank: Rank) -> &'static str {
+    match rank {
+        Rank::Ace | Rank::Eight => "an",
+        _ => "a",
+    }
+}
+
+// "Ace, King and Queen"
+fn list(ranks: &[Rank]) -> String {
+    match ranks {
+        [] => String::new(),
+        [rank] => rank.name().to_string(),
+        [init @ .., last] => format!("{} and {}", init.iter().map(|r| r.name()).collect::<Vec<_>>().join(", "), last.name()),
+    }
+}
+
+fn dashed(ranks: &[Rank]) -> String {
+    ranks.iter().map(|r| r.name()).collect::<Vec<_>>().join("-")
+}
+
+fn kickers(ranks: &[Rank]) -> String {
+    match ranks {
+        [kicker] => format!("with {} {} kicker", article(*kicker), kicker.name()),
+        _ => format!("with {} kickers", list(ranks)),
+    }
+}
+
+impl FormattedHand<'_> {
+    fn short(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let join = |ranks: &[Rank]| ranks.iter().map(|r| r.to_string()).collect::<Vec<_>>().join(",");
+
+        match self.hand {
+            Hand::HighCard(cards) => write!(f, "highcard ({})", join(cards)),
             Hand::Pairs(Pairs{pairs, kickers}) => {
                 match pairs.len() { 
-                    1 => write!(f, "pair {} ({}, {}, {})", cts(pairs[0]), cts(kickers[0]), cts(kickers[1]), cts(kickers[2])),
-                    2 => write!(f, "pairs {} + {} ({})", cts(pairs[0]), cts(pairs[1]), cts(kickers[0])),
+                    1 => write!(f, "pair {} ({}, {}, {})", pairs[0], kickers[0], kickers[1], kickers[2]),
+                    2 => write!(f, "pairs {} + {} ({})", pairs[0], pairs[1], kickers[0]),
                      _ => panic!(),
                 }
             }
-            Hand::Set(set) => write!(f, "set {} ({}, {})", cts(set.set), cts(set.kickers[0]), cts(set.kickers[1])),
-            Hand::Straight(top) => write!(f, "straight {}-{}", cts(*top as i32 - 4), cts(*top)),
+            Hand::Set(set) => write!(f, "set {} ({}, {})", set.set, set.kickers[0], set.kickers[1]),
+            Hand::Straight(top) => write!(f, "straight {}-{}", straight_bottom(*top, self.variant), top),
             Hand::Flush(Flush{cards, suit}) => {
-                write!(f, "flush (")?;
-                for c in cards {
-                    write!(f, "{},", card_to_string(c + suit * 13))?;
-                }
-                write!(f, "{})", 8u8 as char) // backspace
+                let cards: Vec<String> = cards.iter().map(|c| Card::new(*c, *suit).to_string()).collect();
+                write!(f, "flush ({})", cards.join(","))
             },
             Hand::FullHouse(FullHouse{set, pair}) => write!(f, "fullhouse {} + {}", set, pair),
             Hand::Quads(Quads{quads, kicker}) => write!(f, "quads {} + {}", quads, kicker),
-            Hand::StraightFlush(StraightFlush{top, suit}) => write!(f, "straightflush {}-{}"
-                                                                        , card_to_string( match *top as i32 - 4 < 0 {
-                                                                            true => 12,
-                                                                            false => top - 4
-                                                                        } + suit * 13)
-                                                                        , card_to_string(top + suit * 13)),
+            Hand::StraightFlush(StraightFlush{top, suit}) => write!(f, "straightflush {}-{}",
+                                                                        Card::new(straight_bottom(*top, self.variant), *suit),
+                                                                        Card::new(*top, *suit)),
+        }
+    }
+
+    fn long(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let category = self.hand.category();
+
+        match self.hand {
+            Hand::HighCard(cards) => write!(f, "{}, {}", category, dashed(cards)),
+            Hand::Pairs(Pairs{pairs, kickers: k}) if pairs.len() == 1 => write!(f, "{}, {} {}", category, pairs[0].plural(), kickers(k)),
+            Hand::Pairs(Pairs{pairs, kickers: k}) => write!(f, "{}, {} and {} {}", category, pairs[0].plural(), pairs[1].plural(), kickers(k)),
+            Hand::Set(Set{set, kickers: k}) => write!(f, "{}, {} {}", category, set.plural(), kickers(k)),
+            Hand::Straight(top) => write!(f, "{}, {} high", category, top.name()),
+            Hand::Flush(Flush{cards, suit}) => write!(f, "{}, {} of {}", category, dashed(cards), suit.name()),
+            Hand::FullHouse(FullHouse{set, pair}) => write!(f, "{}, {} full of {}", category, set.plural(), pair.plural()),
+            Hand::Quads(Quads{quads, kicker}) => write!(f, "{}, {} {}", category, quads.plural(), kickers(&[*kicker])),
+            Hand::StraightFlush(StraightFlush{top: Rank::Ace, suit}) => write!(f, "Royal Flush of {}", suit.name()),
+            Hand::StraightFlush(StraightFlush{top, suit}) => write!(f, "{}, {} high of {}", category, top.name(), suit.name()),
+        }
+    }
+
+    fn machine(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:", self.hand.category().key())?;
+        for rank in self.hand.ranks_variant(self.variant) {
+            write!(f, "{}", rank)?;
+        }
+        match self.hand.suit() {
+            Some(suit) => write!(f, ":{}", suit),
+            None => Ok(()),
         }
     }
 }
 
-type HighCard = [u8; 5];
+impl Display for FormattedHand<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.format {
+            HandFormat::Short => self.short(f),
+            HandFormat::Long => self.long(f),
+            HandFormat::Machine => self.machine(f),
+        }
+    }
+}
 
-#[derive(Debug, PartialEq)]
-struct Pairs {
-    pairs: Vec<u8>,
-    kickers: Vec<u8>,
+impl Display for Hand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.format(HandFormat::Short).fmt(f)
+    }
 }
 
-impl PartialOrd for Pairs {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        match self.pairs.len().partial_cmp(&other.pairs.len()) {
-            Some(Ordering::Equal) => {},
-            order => return order,
+// Dense index of a hand among the 7462 distinct five card hands, from 1 for 7-5-4-3-2
+// up to 7462 for a royal flush. Hands with equal strengths are equal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(try_from = "u16", into = "u16"))]
+pub struct Strength(u16);
+
+impl TryFrom<u16> for Strength {
+    type Error = String;
+
+    fn try_from(value: u16) -> Result<Self, Self::Error> {
+        Strength::new(value).ok_or_else(|| format!("strength {} is outside of 1..=7462", value))
+    }
+}
+
+impl From<Strength> for u16 {
+    fn from(strength: Strength) -> u16 {
+        strength.0
+    }
+}
+
+// Number of distinct hands, first strength and number of five card hands of every strength per category
+const CATEGORY_CLASSES: [(u16, u16, u32); 9] = [
+    (1277, 1, 1020),
+    (2860, 1278, 384),
+    (858, 4138, 144),
+    (858, 4996, 64),
+    (10, 5854, 1020),
+    (1277, 5864, 4),
+    (156, 7141, 24),
+    (156, 7297, 4),
+    (10, 7453, 4),
+];
+
+const FIVE_CARD_HANDS: u32 = 2598960;
+
+impl Strength {
+    pub const MIN: Strength = Strength(1);
+    pub const MAX: Strength = Strength(7462);
+
+    pub fn new(value: u16) -> Option<Strength> {
+        match value {
+            1..=7462 => Some(Strength(value)),
+            _ => None,
         }
+    }
+
+    pub fn value(self) -> u16 {
+        self.0
+    }
+
+    pub fn category(self) -> Category {
+        let index = CATEGORY_CLASSES.iter().rposition(|(_, first, _)| *first <= self.0).unwrap();
+        CATEGORY_ORDER[index]
+    }
+
+    // Fraction of all five card hands that are strictly weaker
+    pub fn percentile(self) -> f64 {
+        let category = self.category() as usize;
+        let (_, first, combos) = CATEGORY_CLASSES[category];
+        let weaker: u32 = CATEGORY_CLASSES[..category].iter().map(|(classes, _, combos)| *classes as u32 * combos).sum::<u32>()
+            + (self.0 - first) as u32 * combos;
+        weaker as f64 / FIVE_CARD_HANDS as f64
+    }
+}
+
+const CATEGORY_ORDER: [Category; 9] = [
+    Category::HighCard,
+    Category::Pair,
+    Category::TwoPairs,
+    Category::Set,
+    Category::Straight,
+    Category::Flush,
+    Category::FullHouse,
+    Category::Quads,
+    Category::StraightFlush,
+];
+
+// Colexicographic index of a set of distinct ranks given from the highest to the lowest
+fn colex(ranks: &[u8]) -> u16 {
+    ranks.iter().enumerate().map(|(i, rank)| binomial(*rank as u32, (ranks.len() - i) as u32) as u16).sum()
+}
 
-        match self.pairs.partial_cmp(&other.pairs) {
-            Some(Ordering::Equal) => {},
-            order => return order,
+// Inverse of colex for a set of size ranks.len()
+fn uncolex(mut index: u16, ranks: &mut [u8]) {
+    let size = ranks.len();
+    for (i, rank) in ranks.iter_mut().enumerate() {
+        let k = (size - i) as u32;
+        let mut r = 12;
+        while binomial(r, k) as u16 > index {
+            r -= 1;
         }
+        *rank = r as u8;
+        index -= binomial(r, k) as u16;
+    }
+}
 
-        assert_eq!(self.kickers.len(), other.kickers.len());
+// Colexicographic indices of the ten straights in ascending order, the wheel comes first
+fn straight_indices() -> [u16; 10] {
+    let mut indices = [0; 10];
+    indices[0] = colex(&[12, 3, 2, 1, 0]);
+    for top in 4..13u8 {
+        indices[top as usize - 3] = colex(&[top, top - 1, top - 2, top - 3, top - 4]);
+    }
+    indices.sort_unstable();
+    indices
+}
+
+// Index of five distinct ranks among the 1277 sets that do not form a straight
+fn unpaired_index(ranks: &[Rank; 5]) -> u16 {
+    let index = colex(&ranks.map(Rank::index));
+    index - straight_indices().iter().filter(|straight| **straight < index).count() as u16
+}
 
-        self.kickers.partial_cmp(&other.kickers)
+fn unpaired_ranks(mut index: u16) -> [Rank; 5] {
+    for straight in straight_indices() {
+        if straight <= index {
+            index += 1;
+        }
     }
+    let mut ranks = [0; 5];
+    uncolex(index, &mut ranks);
+    ranks.map(|rank| Rank::from_index(rank).unwrap())
 }
 
-#[derive(Debug, PartialEq, PartialOrd)]
-struct Set {
-    set: u8,
-    kickers: [u8; 2],
+// Kicker ranks shifted down to close the gaps left by the excluded ranks
+fn compress(ranks: &[Rank], excluded: &[Rank]) -> Vec<u8> {
+    ranks.iter().map(|rank| rank.index() - excluded.iter().filter(|e| *e < rank).count() as u8).collect()
 }
 
-type Straight = u8;
+fn expand(indices: &[u8], excluded: &[Rank]) -> Vec<Rank> {
+    let mut excluded: Vec<u8> = excluded.iter().map(|rank| rank.index()).collect();
+    excluded.sort_unstable();
+    indices.iter().map(|index| {
+        let mut rank = *index;
+        for e in &excluded {
+            if rank >= *e {
+                rank += 1;
+            }
+        }
+        Rank::from_index(rank).unwrap()
+    }).collect()
+}
+
+impl Hand {
+    pub fn strength(&self) -> Strength {
+        let index = match self {
+            Hand::HighCard(cards) => unpaired_index(cards),
+            Hand::Pairs(Pairs{pairs, kickers}) if pairs.len() == 1 => {
+                pairs[0].index() as u16 * 220 + colex(&compress(kickers, pairs))
+            },
+            Hand::Pairs(Pairs{pairs, kickers}) => {
+                colex(&[pairs[0].index(), pairs[1].index()]) * 11 + compress(kickers, pairs)[0] as u16
+            },
+            Hand::Set(Set{set, kickers}) => set.index() as u16 * 66 + colex(&compress(kickers, &[*set])),
+            Hand::Straight(top) => top.index() as u16 - 3,
+            Hand::Flush(Flush{cards, ..}) => unpaired_index(cards),
+            Hand::FullHouse(FullHouse{set, pair}) => set.index() as u16 * 12 + compress(&[*pair], &[*set])[0] as u16,
+            Hand::Quads(Quads{quads, kicker}) => quads.index() as u16 * 12 + compress(&[*kicker], &[*quads])[0] as u16,
+            Hand::StraightFlush(StraightFlush{top, ..}) => top.index() as u16 - 3,
+        };
+        Strength(CATEGORY_CLASSES[self.category() as usize].1 + index)
+    }
+
+    // Flushes and straight flushes come back as spades since strengths do not keep suits
+    pub fn from_strength(strength: Strength) -> Hand {
+        let category = strength.category();
+        let index = strength.0 - CATEGORY_CLASSES[category as usize].1;
+        let rank = |index: u16| Rank::from_index(index as u8).unwrap();
+
+        match category {
+            Category::HighCard => Hand::highcard(&unpaired_ranks(index)),
+            Category::Pair => {
+                let pair = rank(index / 220);
+                let mut kickers = [0; 3];
+                uncolex(index % 220, &mut kickers);
+                Hand::pairs(&[pair], &expand(&kickers, &[pair]))
+            },
+            Category::TwoPairs => {
+                let mut pairs = [0; 2];
+                uncolex(index / 11, &mut pairs);
+                let pairs = pairs.map(|pair| Rank::from_index(pair).unwrap());
+                Hand::pairs(&pairs, &expand(&[(index % 11) as u8], &pairs))
+            },
+            Category::Set => {
+                let set = rank(index / 66);
+                let mut kickers = [0; 2];
+                uncolex(index % 66, &mut kickers);
+                Hand::set(set, &expand(&kickers, &[set]))
+            },
+            Category::Straight => Hand::straight(rank(index + 3)),
+            Category::Flush => Hand::flush(&unpaired_ranks(index), Suit::Spades),
+            Category::FullHouse => {
+                let set = rank(index / 12);
+                Hand::fullhouse(set, expand(&[(index % 12) as u8], &[set])[0])
+            },
+            Category::Quads => {
+                let quads = rank(index / 12);
+                Hand::quads(quads, expand(&[(index % 12) as u8], &[quads])[0])
+            },
+            Category::StraightFlush => Hand::straightflush(rank(index + 3), Suit::Spades),
+        }
+    }
+}
+
+// Deserialised hands go through the same checks the constructors make, since strength
+// and Display rely on them. Ranks of each group have to be distinct and descending.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+#[serde(tag = "category", content = "value", rename_all = "snake_case")]
+enum UncheckedHand {
+    HighCard(HighCard),
+    Pairs(Pairs),
+    Set(Set),
+    Straight(Straight),
+    Flush(Flush),
+    FullHouse(FullHouse),
+    Quads(Quads),
+    StraightFlush(StraightFlush),
+}
 
-#[derive(Debug)]
-struct Flush {
-    cards: [u8; 5],
-    suit: u8,
+#[cfg(feature = "serde")]
+fn descending(ranks: &[Rank]) -> bool {
+    ranks.windows(2).all(|pair| pair[0] > pair[1])
+}
+
+#[cfg(feature = "serde")]
+fn is_straight(ranks: &[Rank; 5]) -> bool {
+    ranks[0].index() - ranks[4].index() == 4 || *ranks == [Rank::Ace, Rank::Five, Rank::Four, Rank::Three, Rank::Two]
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<UncheckedHand> for Hand {
+    type Error = String;
+
+    fn try_from(hand: UncheckedHand) -> Result<Self, Self::Error> {
+        let valid = match &hand {
+            UncheckedHand::HighCard(cards) | UncheckedHand::Flush(Flush{cards, ..}) => descending(cards) && !is_straight(cards),
+            UncheckedHand::Pairs(Pairs{pairs, kickers}) => {
+                (1..=2).contains(&pairs.len())
+                    && pairs.len() * 2 + kickers.len() == 5
+                    && descending(pairs)
+                    && descending(kickers)
+                    && kickers.iter().all(|kicker| !pairs.contains(kicker))
+            },
+            UncheckedHand::Set(Set{set, kickers}) => descending(kickers) && !kickers.contains(set),
+            UncheckedHand::Straight(top) | UncheckedHand::StraightFlush(StraightFlush{top, ..}) => *top >= Rank::Five,
+            UncheckedHand::FullHouse(FullHouse{set, pair}) => set != pair,
+            UncheckedHand::Quads(Quads{quads, kicker}) => quads != kicker,
+        };
+        if !valid {
+            return Err("ranks do not form a valid hand of the category".to_string());
+        }
+
+        Ok(match hand {
+            UncheckedHand::HighCard(cards) => Hand::HighCard(cards),
+            UncheckedHand::Pairs(pairs) => Hand::Pairs(pairs),
+            UncheckedHand::Set(set) => Hand::Set(set),
+            UncheckedHand::Straight(top) => Hand::Straight(top),
+            UncheckedHand::Flush(flush) => Hand::Flush(flush),
+            UncheckedHand::FullHouse(fullhouse) => Hand::FullHouse(fullhouse),
+            UncheckedHand::Quads(quads) => Hand::Quads(quads),
+            UncheckedHand::StraightFlush(straightflush) => Hand::StraightFlush(straightflush),
+        })
+    }
+}
+
+pub type HighCard = [Rank; 5];
+
+// Two pairs beat one pair, then the pairs are compared from the highest one and the kickers last.
+// Both hands have the same number of kickers once the number of pairs is equal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Pairs {
+    pairs: Vec<Rank>,
+    kickers: Vec<Rank>,
+}
+
+impl Ord for Pairs {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.pairs.len().cmp(&other.pairs.len())
+            .then_with(|| self.pairs.cmp(&other.pairs))
+            .then_with(|| self.kickers.cmp(&other.kickers))
+    }
+}
+
+impl PartialOrd for Pairs {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Set {
+    set: Rank,
+    kickers: [Rank; 2],
+}
+
+pub type Straight = Rank;
+
+// The suit is only kept for display, flushes of different suits with the same ranks are equal
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Flush {
+    cards: [Rank; 5],
+    suit: Suit,
 }
 
 impl PartialEq for Flush {
     fn eq(&self, other: &Self) -> bool {
         self.cards.eq(&other.cards)
     }
-    fn ne(&self, other: &Self) -> bool {
-        self.cards.ne(&other.cards)
+}
+
+impl Eq for Flush {}
+
+impl Ord for Flush {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.cards.cmp(&other.cards)
     }
 }
 
 impl PartialOrd for Flush {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.cards.partial_cmp(&other.cards)
+        Some(self.cmp(other))
     }
 }
 
 // Default PartialOrd implementation automatically prioritizes
 // the first property of the struct so set will be checked first
-#[derive(Debug, PartialEq, PartialOrd)]
-struct FullHouse {
-    set: u8,
-    pair: u8,
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FullHouse {
+    set: Rank,
+    pair: Rank,
 }
 
-#[derive(Debug, PartialEq, PartialOrd)]
-struct Quads {
-    quads: u8,
-    kicker: u8,
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Quads {
+    quads: Rank,
+    kicker: Rank,
 }
 
-#[derive(Debug)]
-struct StraightFlush {
-    top: u8,
-    suit: u8,
+// Like Flush the suit does not take part in comparisons
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StraightFlush {
+    top: Rank,
+    suit: Suit,
 }
 
 impl PartialEq for StraightFlush {
     fn eq(&self, other: &Self) -> bool {
         self.top.eq(&other.top)
     }
-    fn ne(&self, other: &Self) -> bool {
-        self.top.ne(&other.top)
+}
+
+impl Eq for StraightFlush {}
+
+impl Ord for StraightFlush {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.top.cmp(&other.top)
     }
 }
 
 impl PartialOrd for StraightFlush {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.top.partial_cmp(&other.top)
+        Some(self.cmp(other))
     }
 }
 
 #[cfg(test)]
 mod test {
-    use super::Hand;
+    use super::{Category, Hand, HandFormat, Strength};
+    use crate::deck::{Rank::*, Suit};
+    use crate::variant::Variant;
+    use std::cmp::Ordering;
+    use std::collections::BTreeMap;
+
     #[test]
     fn compare_highcard_pair() {
-        let highcard = Hand::highcard(&[12,11,10,9,8]);
-        let pair = Hand::pairs(&vec![2], &vec![3,4,5]);
+        let highcard = Hand::highcard(&[Ace, King, Queen, Jack, Nine]);
+        let pair = Hand::pairs(&[Four], &[Five, Six, Seven]);
         assert!(pair > highcard);
     }
 
     #[test]
     fn compare_pair_set() {
-        let set = Hand::set(2, &[3, 4]);
-        let pair = Hand::pairs(&vec![13, 12], &vec![11]);
+        let set = Hand::set(Four, &[Five, Six]);
+        let pair = Hand::pairs(&[Ace, King], &[Queen]);
         assert!(set > pair);
     }
 
     #[test]
     fn compare_pairs() {
-        let a = Hand::pairs(&[10], &[1, 4, 5]);
-        let b = Hand::pairs(&[0, 1], &[3]);
+        let a = Hand::pairs(&[Queen], &[Three, Six, Seven]);
+        let b = Hand::pairs(&[Two, Three], &[Five]);
         assert!(a < b);
     }
 
     #[test]
     fn compare_fullhouses() {
-        let a = Hand::fullhouse(8, 10);
-        let b = Hand::fullhouse(10, 8);
+        let a = Hand::fullhouse(Ten, Queen);
+        let b = Hand::fullhouse(Queen, Ten);
         assert!(a < b);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn display_without_control_characters() {
+        assert_eq!(Hand::highcard(&[Ace, King, Queen, Jack, Nine]).to_string(), "highcard (A,K,Q,J,9)");
+        assert_eq!(Hand::flush(&[Ace, Jack, Nine, Seven, Six], Suit::Hearts).to_string(), "flush (Ah,Jh,9h,7h,6h)");
+    }
+
+    #[test]
+    fn long_format() {
+        let long = |hand: Hand| hand.format(HandFormat::Long).to_string();
+        assert_eq!(long(Hand::highcard(&[Ace, King, Queen, Jack, Nine])), "High Card, Ace-King-Queen-Jack-Nine");
+        assert_eq!(long(Hand::pairs(&[Seven], &[Ace, Ten, Four])), "One Pair, Sevens with Ace, Ten and Four kickers");
+        assert_eq!(long(Hand::pairs(&[King, Seven], &[Ace])), "Two Pair, Kings and Sevens with an Ace kicker");
+        assert_eq!(long(Hand::set(Six, &[Queen, Two])), "Three of a Kind, Sixes with Queen and Two kickers");
+        assert_eq!(long(Hand::straight(Five)), "Straight, Five high");
+        assert_eq!(long(Hand::flush(&[Ace, Jack, Nine, Seven, Six], Suit::Hearts)), "Flush, Ace-Jack-Nine-Seven-Six of Hearts");
+        assert_eq!(long(Hand::fullhouse(Ten, Eight)), "Full House, Tens full of Eights");
+        assert_eq!(long(Hand::quads(Two, Eight)), "Four of a Kind, Twos with an Eight kicker");
+        assert_eq!(long(Hand::straightflush(Nine, Suit::Clubs)), "Straight Flush, Nine high of Clubs");
+        assert_eq!(long(Hand::straightflush(Ace, Suit::Spades)), "Royal Flush of Spades");
+    }
+
+    #[test]
+    fn machine_format() {
+        let machine = |hand: Hand| hand.format(HandFormat::Machine).to_string();
+        assert_eq!(machine(Hand::pairs(&[King, Seven], &[Ace])), "two_pairs:KK77A");
+        assert_eq!(machine(Hand::fullhouse(Ten, Eight)), "full_house:TTT88");
+        assert_eq!(machine(Hand::straight(Five)), "straight:5432A");
+        let short_deck = |hand: Hand| hand.format_variant(HandFormat::Machine, Variant::ShortDeck).to_string();
+        assert_eq!(short_deck(Hand::straight(Nine)), "straight:9876A");
+        assert_eq!(short_deck(Hand::straight(Ten)), "straight:T9876");
+        assert_eq!(machine(Hand::flush(&[Ace, Jack, Nine, Seven, Six], Suit::Hearts)), "flush:AJ976:h");
+        assert_eq!(Hand::set(Six, &[Queen, Two]).format(HandFormat::Short).to_string(), "set 6 (Q, 2)");
+    }
+
+    #[test]
+    fn display_uses_rank_letters() {
+        assert_eq!(Hand::fullhouse(Ten, Eight).to_string(), "fullhouse T + 8");
+        assert_eq!(Hand::quads(Ace, Two).to_string(), "quads A + 2");
+        assert_eq!(Hand::straight(Five).to_string(), "straight A-5");
+        assert_eq!(Hand::straightflush(Nine, Suit::Hearts).to_string(), "straightflush 5h-9h");
+        let short_deck = |hand: Hand| hand.format_variant(HandFormat::Short, Variant::ShortDeck).to_string();
+        assert_eq!(short_deck(Hand::straight(Nine)), "straight A-9");
+        assert_eq!(short_deck(Hand::straightflush(Nine, Suit::Hearts)), "straightflush Ah-9h");
+    }
+
+    // One or more hands per category in strictly ascending order
+    fn ascending_hands() -> Vec<Hand> {
+        vec![
+            Hand::highcard(&[Seven, Five, Four, Three, Two]),
+            Hand::highcard(&[King, Queen, Jack, Ten, Eight]),
+            Hand::highcard(&[Ace, King, Queen, Jack, Nine]),
+            Hand::pairs(&[Two], &[Five, Four, Three]),
+            Hand::pairs(&[Two], &[Ace, King, Queen]),
+            Hand::pairs(&[Ace], &[Four, Three, Two]),
+            Hand::pairs(&[Ace], &[King, Queen, Jack]),
+            Hand::pairs(&[Three, Two], &[Four]),
+            Hand::pairs(&[Three, Two], &[Ace]),
+            Hand::pairs(&[Ace, Two], &[Three]),
+            Hand::pairs(&[Ace, King], &[Queen]),
+            Hand::set(Two, &[Four, Three]),
+            Hand::set(Two, &[Ace, King]),
+            Hand::set(Ace, &[King, Queen]),
+            Hand::straight(Five),
+            Hand::straight(Six),
+            Hand::straight(Ace),
+            Hand::flush(&[Seven, Five, Four, Three, Two], Suit::Spades),
+            Hand::flush(&[Ace, King, Queen, Jack, Nine], Suit::Clubs),
+            Hand::fullhouse(Two, Three),
+            Hand::fullhouse(Two, Ace),
+            Hand::fullhouse(Three, Two),
+            Hand::fullhouse(Ace, King),
+            Hand::quads(Two, Three),
+            Hand::quads(Two, Ace),
+            Hand::quads(Ace, King),
+            Hand::straightflush(Five, Suit::Hearts),
+            Hand::straightflush(King, Suit::Diamonds),
+            Hand::straightflush(Ace, Suit::Spades),
+        ]
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let hand = Hand::pairs(&[King, Seven], &[Ace]);
+        let json = serde_json::to_string(&hand).unwrap();
+        assert_eq!(json, r#"{"category":"pairs","value":{"pairs":["K","7"],"kickers":["A"]}}"#);
+        let json = serde_json::to_string(&Hand::straight(Five)).unwrap();
+        assert_eq!(json, r#"{"category":"straight","value":"5"}"#);
+        let json = serde_json::to_string(&Hand::flush(&[Ace, Jack, Nine, Seven, Six], Suit::Hearts)).unwrap();
+        assert_eq!(json, r#"{"category":"flush","value":{"cards":["A","J","9","7","6"],"suit":"h"}}"#);
+
+        for hand in ascending_hands() {
+            let json = serde_json::to_string(&hand).unwrap();
+            let back: Hand = serde_json::from_str(&json).unwrap();
+            assert_eq!(back, hand);
+            assert_eq!(back.suit(), hand.suit());
+        }
+
+        let strength = Hand::fullhouse(Ten, Eight).strength();
+        assert_eq!(serde_json::from_str::<Strength>(&serde_json::to_string(&strength).unwrap()).unwrap(), strength);
+        assert!(serde_json::from_str::<Strength>("7463").is_err());
+
+        for json in [
+            r#"{"category":"pairs","value":{"pairs":["K","7","2"],"kickers":[]}}"#,
+            r#"{"category":"pairs","value":{"pairs":["7","K"],"kickers":["A"]}}"#,
+            r#"{"category":"pairs","value":{"pairs":["K"],"kickers":["A","K","2"]}}"#,
+            r#"{"category":"pairs","value":{"pairs":["K"],"kickers":["A"]}}"#,
+            r#"{"category":"high_card","value":["2","7","9","J","K"]}"#,
+            r#"{"category":"high_card","value":["9","8","7","6","5"]}"#,
+            r#"{"category":"flush","value":{"cards":["A","A","9","7","6"],"suit":"h"}}"#,
+            r#"{"category":"set","value":{"set":"K","kickers":["K","2"]}}"#,
+            r#"{"category":"straight","value":"4"}"#,
+            r#"{"category":"full_house","value":{"set":"K","pair":"K"}}"#,
+        ] {
+            assert!(serde_json::from_str::<Hand>(json).is_err(), "{}", json);
+        }
+    }
+
+    #[test]
+    fn compare_all_categories() {
+        let hands = ascending_hands();
+        for (i, a) in hands.iter().enumerate() {
+            for (j, b) in hands.iter().enumerate() {
+                assert_eq!(a.cmp(b), i.cmp(&j), "{} vs {}", a, b);
+                assert_eq!(a.partial_cmp(b), Some(i.cmp(&j)));
+            }
+        }
+    }
+
+    #[test]
+    fn sort_and_collect_hands() {
+        let mut hands = ascending_hands();
+        hands.reverse();
+        hands.sort();
+        assert_eq!(hands, ascending_hands());
+        assert_eq!(hands.iter().max(), Some(&Hand::straightflush(Ace, Suit::Spades)));
+
+        let categories: BTreeMap<Hand, Category> = hands.iter().map(|hand| (hand.clone(), hand.category())).collect();
+        assert_eq!(categories.len(), hands.len());
+        assert_eq!(categories.keys().next(), Some(&Hand::highcard(&[Seven, Five, Four, Three, Two])));
+    }
+
+    #[test]
+    fn suits_do_not_matter() {
+        let a = Hand::flush(&[Ace, Ten, Eight, Five, Two], Suit::Spades);
+        let b = Hand::flush(&[Ace, Ten, Eight, Five, Two], Suit::Hearts);
+        assert_eq!(a.cmp(&b), Ordering::Equal);
+        assert_eq!(a, b);
+        assert_eq!(Hand::straightflush(Nine, Suit::Clubs), Hand::straightflush(Nine, Suit::Diamonds));
+    }
+
+    #[test]
+    fn hand_ranks() {
+        assert_eq!(Hand::pairs(&[King, Seven], &[Ace]).ranks(), [King, King, Seven, Seven, Ace]);
+        assert_eq!(Hand::straight(Five).ranks(), [Five, Four, Three, Two, Ace]);
+        assert_eq!(Hand::straight(Nine).ranks_variant(Variant::ShortDeck), [Nine, Eight, Seven, Six, Ace]);
+        assert_eq!(Hand::fullhouse(Ten, Eight).ranks(), [Ten, Ten, Ten, Eight, Eight]);
+        assert_eq!(Hand::straightflush(King, Suit::Clubs).suit(), Some(Suit::Clubs));
+        assert_eq!(Hand::straight(King).suit(), None);
+    }
+
+    #[test]
+    fn strength_round_trip() {
+        let mut previous: Option<Hand> = None;
+        for value in 1..=7462 {
+            let strength = Strength::new(value).unwrap();
+            let hand = Hand::from_strength(strength);
+            assert_eq!(hand.strength(), strength, "{}", hand);
+            assert_eq!(hand.category(), strength.category());
+            if let Some(previous) = previous {
+                assert!(previous < hand, "{} < {}", previous, hand);
+            }
+            previous = Some(hand);
+        }
+        assert_eq!(Strength::new(0), None);
+        assert_eq!(Strength::new(7463), None);
+    }
+
+    #[test]
+    fn strength_follows_hand_order() {
+        let hands = ascending_hands();
+        assert_eq!(hands[0].strength(), Strength::MIN);
+        assert_eq!(hands.last().unwrap().strength(), Strength::MAX);
+        assert!(hands.windows(2).all(|w| w[0].strength() < w[1].strength()));
+    }
+
+    #[test]
+    fn strength_percentile() {
+        assert_eq!(Strength::MIN.percentile(), 0.0);
+        assert_eq!(Strength::MAX.percentile(), 2598956.0 / 2598960.0);
+        // Every hand below a pair of twos is a high card hand
+        let pair = Hand::pairs(&[Two], &[Five, Four, Three]).strength();
+        assert_eq!(pair.percentile(), 1302540.0 / 2598960.0);
+    }
+}
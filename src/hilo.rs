@@ -0,0 +1,95 @@
+use crate::analyser::{self, AnalyseError};
+use crate::combinations::Combinations;
+use crate::deck::{Card, Deck, HoleBoardError, Rank};
+use crate::hand::Hand;
+use crate::lowball::{self, AceToFive};
+use crate::omaha::{self, Omaha};
+
+// Split pot games give half the pot to the best high hand and half to the best low,
+// as long as some low qualifies. Otherwise the high hand takes everything.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HiLo {
+    pub high: Hand,
+    pub low: Option<AceToFive>,
+}
+
+// Best ace-to-five low made of five different ranks from eight down to ace, if there is one
+pub fn eight_or_better(deck: Deck) -> Option<AceToFive> {
+    let mut low = 0;
+    let mut ranks = 0;
+    for rank in [Rank::Ace, Rank::Two, Rank::Three, Rank::Four, Rank::Five, Rank::Six, Rank::Seven, Rank::Eight] {
+        if ranks == 5 {
+            break;
+        }
+        if let Some(card) = (0..4).map(|suit| rank.index() + 13 * suit).find(|i| deck & (1 << i) != 0) {
+            low |= Card::from_index(card).unwrap().to_deck();
+            ranks += 1;
+        }
+    }
+
+    match ranks {
+        5 => Some(lowball::ace_to_five(low)),
+        _ => None,
+    }
+}
+
+// Stud Hi-Lo and any other game where the best five of all cards play for both halves
+pub fn stud(deck: Deck) -> Result<HiLo, AnalyseError> {
+    Ok(HiLo {
+        high: analyser::try_analyse(deck)?,
+        low: eight_or_better(deck),
+    })
+}
+
+// Omaha Hi-Lo, where the high and the low may use different hole cards
+// but each uses exactly two of them and three of the board
+pub fn omaha(game: Omaha, hole: Deck, board: Deck) -> Result<HiLo, HoleBoardError> {
+    let high = omaha::analyse(game, hole, board)?;
+    let low = Combinations::new(hole, 2)
+        .flat_map(|two| Combinations::new(board, 3).map(move |three| two | three))
+        .filter_map(eight_or_better)
+        .max();
+    Ok(HiLo { high, low })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::deck::{cards, Rank::*};
+
+    #[test]
+    fn test_eight_or_better() {
+        assert_eq!(eight_or_better(cards(&["Ah", "2c", "3d", "4s", "5h", "Kd", "Kc"])).unwrap().to_string(), "5-4-3-2-A");
+        assert_eq!(eight_or_better(cards(&["8h", "7c", "6d", "4s", "2h", "2d", "Kc"])).unwrap().to_string(), "8-7-6-4-2");
+        assert_eq!(eight_or_better(cards(&["9h", "7c", "6d", "4s", "2h", "2d", "Kc"])), None);
+        assert_eq!(eight_or_better(cards(&["Ah", "2c", "3d", "4s"])), None);
+    }
+
+    #[test]
+    fn test_stud() {
+        let hilo = stud(cards(&["Ah", "2h", "3h", "4h", "6h", "Kd", "Kc"])).unwrap();
+        assert_eq!(hilo.high.category(), crate::hand::Category::Flush);
+        assert_eq!(hilo.low.unwrap().ranks(), [Six, Four, Three, Two, Ace]);
+        assert_eq!(stud(cards(&["Ah", "2h"])), Err(AnalyseError::NotEnoughCards(2)));
+    }
+
+    #[test]
+    fn test_omaha_low_needs_two_hole_cards() {
+        // Three low cards on the board and only one low card in the hole
+        let board = cards(&["2c", "5d", "7h", "Kd", "Qs"]);
+        let hilo = omaha(Omaha::Plo4, cards(&["Ah", "Kc", "Ks", "9h"]), board).unwrap();
+        assert_eq!(hilo.low, None);
+        assert_eq!(hilo.high, Hand::set(King, &[Queen, Seven]));
+
+        let hilo = omaha(Omaha::Plo4, cards(&["Ah", "3c", "Ks", "9h"]), board).unwrap();
+        assert_eq!(hilo.low.unwrap().to_string(), "7-5-3-2-A");
+    }
+
+    #[test]
+    fn test_omaha_low_with_different_hole_cards() {
+        let board = cards(&["2c", "4d", "8h", "Kd", "Ks"]);
+        let hilo = omaha(Omaha::Plo4, cards(&["Ah", "3c", "Kc", "9h"]), board).unwrap();
+        assert_eq!(hilo.high, Hand::set(King, &[Ace, Eight]));
+        assert_eq!(hilo.low.unwrap().to_string(), "8-4-3-2-A");
+    }
+}
@@ -0,0 +1,142 @@
+use crate::combinations::Combinations;
+use crate::deck::{Deck, Suit, FULL_DECK};
+use std::collections::HashMap;
+
+// Suits carry no value of their own, so relabelling them never changes how hands compare.
+// Every (hole cards, board) situation belongs to a class of situations that only differ
+// by such a relabelling and the class is represented by its smallest member.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Canonical {
+    pub hole: Deck,
+    pub board: Deck,
+    // Number of distinct situations in the class
+    pub multiplicity: u32,
+}
+
+const fn suit_permutations() -> [[u8; 4]; 24] {
+    let mut permutations = [[0; 4]; 24];
+    let mut count = 0;
+    let mut i = 0;
+    while i < 256 {
+        let permutation = [(i & 3) as u8, (i >> 2 & 3) as u8, (i >> 4 & 3) as u8, (i >> 6 & 3) as u8];
+        let mut used = 0;
+        let mut s = 0;
+        while s < 4 {
+            used |= 1 << permutation[s];
+            s += 1;
+        }
+        if used == 0xf {
+            permutations[count] = permutation;
+            count += 1;
+        }
+        i += 1;
+    }
+    permutations
+}
+
+static SUIT_PERMUTATIONS: [[u8; 4]; 24] = suit_permutations();
+
+// Moves the cards of suit s to suit permutation[s]
+pub fn permute_suits(deck: Deck, permutation: [Suit; 4]) -> Deck {
+    permute(deck, &permutation.map(Suit::index))
+}
+
+fn permute(deck: Deck, permutation: &[u8; 4]) -> Deck {
+    let mut permuted = 0;
+    for (from, to) in permutation.iter().enumerate() {
+        permuted |= (deck >> (13 * from) & 0x1fff) << (13 * *to as usize);
+    }
+    permuted
+}
+
+pub fn canonicalise(hole: Deck, board: Deck) -> Canonical {
+    assert_eq!((hole | board) & !FULL_DECK, 0, "Deck contains bits outside of the 52 cards");
+    assert_eq!(hole & board, 0, "Detected card that has been dealt more than once");
+
+    let mut images: Vec<(Deck, Deck)> = SUIT_PERMUTATIONS
+        .iter()
+        .map(|permutation| (permute(hole, permutation), permute(board, permutation)))
+        .collect();
+    images.sort_unstable();
+    images.dedup();
+
+    Canonical {
+        hole: images[0].0,
+        board: images[0].1,
+        multiplicity: images.len() as u32,
+    }
+}
+
+// All classes of hole cards of the given size, e.g. the 169 starting hands of Hold'em
+pub fn hole_card_classes(size: u32) -> Vec<Canonical> {
+    collect_classes(Combinations::new(FULL_DECK, size).map(|hole| (hole, 0)))
+}
+
+// All classes of boards of the given size that can be dealt next to the hole cards.
+// Multiplicities only count situations with exactly these hole cards.
+pub fn board_classes(hole: Deck, size: u32) -> Vec<Canonical> {
+    collect_classes(Combinations::new(FULL_DECK & !hole, size).map(|board| (hole, board)))
+}
+
+fn collect_classes<I: Iterator<Item = (Deck, Deck)>>(situations: I) -> Vec<Canonical> {
+    let mut classes: HashMap<(Deck, Deck), u32> = HashMap::new();
+    for (hole, board) in situations {
+        let canonical = canonicalise(hole, board);
+        *classes.entry((canonical.hole, canonical.board)).or_default() += 1;
+    }
+
+    let mut classes: Vec<Canonical> = classes
+        .into_iter()
+        .map(|((hole, board), multiplicity)| Canonical { hole, board, multiplicity })
+        .collect();
+    classes.sort_unstable_by_key(|class| (class.hole, class.board));
+    classes
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::combinations::binomial;
+    use crate::deck::to_deck;
+
+    #[test]
+    fn test_isomorphic_situations() {
+        let a = canonicalise(to_deck(&["As", "Ks"]).unwrap(), to_deck(&["7h", "8h", "2d"]).unwrap());
+        let b = canonicalise(to_deck(&["Ah", "Kh"]).unwrap(), to_deck(&["7s", "8s", "2d"]).unwrap());
+        let c = canonicalise(to_deck(&["Ah", "Kh"]).unwrap(), to_deck(&["7h", "8s", "2d"]).unwrap());
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(a.multiplicity, 24);
+    }
+
+    #[test]
+    fn test_multiplicity() {
+        assert_eq!(canonicalise(to_deck(&["As", "Ad"]).unwrap(), 0).multiplicity, 6);
+        assert_eq!(canonicalise(to_deck(&["As", "Ks"]).unwrap(), 0).multiplicity, 4);
+        assert_eq!(canonicalise(to_deck(&["As", "Kd"]).unwrap(), 0).multiplicity, 12);
+        assert_eq!(canonicalise(0, 0).multiplicity, 1);
+    }
+
+    #[test]
+    fn test_starting_hand_classes() {
+        let classes = hole_card_classes(2);
+        assert_eq!(classes.len(), 169);
+        assert_eq!(classes.iter().map(|class| class.multiplicity as u64).sum::<u64>(), binomial(52, 2));
+        assert!(classes.iter().all(|class| canonicalise(class.hole, 0).multiplicity == class.multiplicity));
+    }
+
+    #[test]
+    fn test_flop_classes() {
+        let hole = to_deck(&["As", "Ks"]).unwrap();
+        let classes = board_classes(hole, 3);
+        assert_eq!(classes.len(), 4494);
+        assert_eq!(classes.iter().map(|class| class.multiplicity as u64).sum::<u64>(), binomial(50, 3));
+    }
+
+    #[test]
+    fn test_permute_suits() {
+        let deck = to_deck(&["As", "2c"]).unwrap();
+        let permuted = permute_suits(deck, [Suit::Diamonds, Suit::Hearts, Suit::Clubs, Suit::Spades]);
+        assert_eq!(permuted, to_deck(&["Ac", "2d"]).unwrap());
+    }
+}
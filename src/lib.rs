@@ -0,0 +1,16 @@
+pub mod hand;
+pub mod deck;
+pub mod analyser;
+pub mod predictor;
+pub mod combinations;
+pub mod isomorphism;
+pub mod dealer;
+pub mod variant;
+pub mod evaluator;
+pub mod lookup;
+pub mod omaha;
+pub mod lowball;
+pub mod hilo;
+pub mod badugi;
+pub mod draws;
+pub mod nuts;
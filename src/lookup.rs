@@ -0,0 +1,345 @@
+use crate::analyser::{self, split_suits};
+use crate::deck::Deck;
+use crate::evaluator::Evaluator;
+use crate::hand::Strength;
+use std::sync::OnceLock;
+
+// Largest number of cards the tables cover
+const MAX_CARDS: usize = 7;
+
+// Evaluates Hold'em hands of 5 to 7 cards with two table lookups instead of a full analysis.
+//
+// Whenever five or more cards share a suit, that suit alone decides the hand: with at most
+// seven cards the other two cannot add up to a full house or quads. Those hands are looked up
+// by the 13 bit mask of the suit. All other hands only depend on how many cards of every rank
+// there are. That multiset is numbered without gaps (a minimal perfect hash) and the number
+// is the index into the rank table of its size.
+//
+// The tables are generated with the analyser the first time an evaluator is created.
+#[derive(Debug, Clone, Copy)]
+pub struct LookupEvaluator {
+    tables: &'static Tables,
+}
+
+#[derive(Debug)]
+struct Tables {
+    // Strength of the best flush in a suit mask, zero for less than five cards
+    flushes: Vec<u16>,
+    // Strength of every rank multiset, indexed by the number of cards and then by hash
+    ranks: Vec<Vec<u16>>,
+    hash: Hash,
+}
+
+impl LookupEvaluator {
+    pub fn new() -> Self {
+        static TABLES: OnceLock<Tables> = OnceLock::new();
+        LookupEvaluator { tables: TABLES.get_or_init(Tables::generate) }
+    }
+
+    pub fn evaluate(&self, deck: Deck) -> Strength {
+        let size = deck.count_ones() as usize;
+        assert!((5..=MAX_CARDS).contains(&size), "Lookup evaluation needs between 5 and 7 cards, got {}", size);
+
+        let suits = split_suits(deck);
+        let value = match suits.iter().find(|suit| suit.count_ones() >= 5) {
+            Some(suit) => self.tables.flushes[*suit as usize],
+            None => {
+                let mut counts = [0; 13];
+                for (i, count) in counts.iter_mut().enumerate() {
+                    *count = suits.iter().map(|suit| (suit >> i & 1) as u8).sum();
+                }
+                self.tables.ranks[size][self.tables.hash.index(&counts, size)]
+            },
+        };
+
+        Strength::new(value).unwrap()
+    }
+}
+
+// Decks evaluated side by side in the batch kernel
+const LANES: usize = 8;
+
+impl LookupEvaluator {
+    // Evaluates every deck of 5 to 7 cards like evaluate. The decks are processed in groups
+    // of LANES with one array per suit and rank. There is no hand written vector code: the
+    // batch only saves the per call work of evaluate and leaves vectorising to the compiler.
+    pub fn evaluate_batch(&self, decks: &[Deck], strengths: &mut [Strength]) {
+        assert_eq!(decks.len(), strengths.len(), "Need one strength for every deck");
+
+        let mut lanes = [0; LANES];
+        let mut values = [0; LANES];
+        for (decks, strengths) in decks.chunks(LANES).zip(strengths.chunks_mut(LANES)) {
+            for (lane, deck) in lanes.iter_mut().zip(decks) {
+                let size = deck.count_ones() as usize;
+                assert!((5..=MAX_CARDS).contains(&size), "Lookup evaluation needs between 5 and 7 cards, got {}", size);
+                *lane = *deck;
+            }
+            // Lanes past the end of the last group repeat its first deck
+            lanes[decks.len()..].fill(decks[0]);
+
+            self.evaluate_lanes(&lanes, &mut values);
+            for (strength, value) in strengths.iter_mut().zip(values) {
+                *strength = Strength::new(value).unwrap();
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn evaluate_lanes(&self, decks: &[Deck; LANES], values: &mut [u16; LANES]) {
+        let mut suits = [[0u16; LANES]; 4];
+        for (lane, deck) in decks.iter().enumerate() {
+            for (suit, ranks) in suits.iter_mut().zip(split_suits(*deck)) {
+                suit[lane] = ranks;
+            }
+        }
+
+        // The mask of the suit with five or more cards, zero without a flush
+        let mut flushes = [0u16; LANES];
+        for suit in suits.iter() {
+            for (flush, suit) in flushes.iter_mut().zip(suit) {
+                *flush |= match suit.count_ones() >= 5 {
+                    true => *suit,
+                    false => 0,
+                };
+            }
+        }
+
+        let mut sizes = [0usize; LANES];
+        for (size, deck) in sizes.iter_mut().zip(decks) {
+            *size = deck.count_ones() as usize;
+        }
+        let mut remaining = sizes;
+        let mut indices = [0u32; LANES];
+        for r in (0..13).rev() {
+            for lane in 0..LANES {
+                let count = suits.iter().map(|suit| (suit[lane] >> r & 1) as usize).sum::<usize>();
+                indices[lane] += self.tables.hash.offsets[r][remaining[lane]][count];
+                remaining[lane] -= count;
+            }
+        }
+
+        for lane in 0..LANES {
+            values[lane] = match flushes[lane] {
+                0 => self.tables.ranks[sizes[lane]][indices[lane] as usize],
+                flush => self.tables.flushes[flush as usize],
+            };
+        }
+    }
+}
+
+impl Default for LookupEvaluator {
+    fn default() -> Self {
+        LookupEvaluator::new()
+    }
+}
+
+impl Evaluator for LookupEvaluator {
+    type Strength = Strength;
+
+    fn evaluate(&self, deck: Deck) -> Strength {
+        LookupEvaluator::evaluate(self, deck)
+    }
+
+    fn evaluate_many(&self, decks: &[Deck], strengths: &mut Vec<Strength>) {
+        let start = strengths.len();
+        strengths.resize(start + decks.len(), Strength::MIN);
+        self.evaluate_batch(decks, &mut strengths[start..]);
+    }
+}
+
+// Numbers the sequences of 13 rank counts between 0 and 4 with a given sum, the count of
+// the ace being the most significant digit
+#[derive(Debug)]
+struct Hash {
+    // offsets[r][remaining][c]: number of sequences that come before the ones where rank r
+    // has count c, when remaining cards are left for the ranks up to r
+    offsets: [[[u32; 5]; MAX_CARDS + 1]; 13],
+}
+
+impl Hash {
+    fn new() -> Self {
+        // sequences[l][s]: number of sequences of l counts that add up to s
+        let mut sequences = [[0u32; MAX_CARDS + 1]; 14];
+        sequences[0][0] = 1;
+        for l in 1..14 {
+            for s in 0..=MAX_CARDS {
+                sequences[l][s] = (0..=s.min(4)).map(|c| sequences[l - 1][s - c]).sum();
+            }
+        }
+
+        let mut offsets = [[[0; 5]; MAX_CARDS + 1]; 13];
+        for (r, offsets) in offsets.iter_mut().enumerate() {
+            for (remaining, offsets) in offsets.iter_mut().enumerate() {
+                for c in 1..5 {
+                    let below = match remaining >= c - 1 {
+                        true => sequences[r][remaining - (c - 1)],
+                        false => 0,
+                    };
+                    offsets[c] = offsets[c - 1] + below;
+                }
+            }
+        }
+
+        Hash { offsets }
+    }
+
+    fn index(&self, counts: &[u8; 13], size: usize) -> usize {
+        let mut remaining = size;
+        let mut index = 0;
+        for r in (0..13).rev() {
+            let count = counts[r] as usize;
+            index += self.offsets[r][remaining][count];
+            remaining -= count;
+        }
+        index as usize
+    }
+}
+
+impl Tables {
+    fn generate() -> Self {
+        let flushes = (0..1 << 13)
+            .map(|suit: Deck| match suit.count_ones() {
+                0..=4 => 0,
+                _ => analyser::analyse(suit).strength().value(),
+            })
+            .collect();
+
+        let hash = Hash::new();
+        let mut ranks = vec![vec![]; MAX_CARDS + 1];
+        for (size, table) in ranks.iter_mut().enumerate().skip(5) {
+            let mut counts = [0; 13];
+            fill_counts(&mut counts, 0, size, &mut |counts| {
+                let index = hash.index(counts, size);
+                if table.len() <= index {
+                    table.resize(index + 1, 0);
+                }
+                table[index] = analyser::analyse(spread_suits(counts)).strength().value();
+            });
+        }
+
+        Tables { flushes, ranks, hash }
+    }
+}
+
+// Calls visit with every way to put the remaining cards on the ranks from r upwards
+fn fill_counts<F: FnMut(&[u8; 13])>(counts: &mut [u8; 13], r: usize, remaining: usize, visit: &mut F) {
+    if r == 13 {
+        if remaining == 0 {
+            visit(counts);
+        }
+        return;
+    }
+    for count in 0..=remaining.min(4) {
+        counts[r] = count as u8;
+        fill_counts(counts, r + 1, remaining - count, visit);
+    }
+    counts[r] = 0;
+}
+
+// A deck with the given rank counts that has no flush. Cards are dealt to the suits in turn,
+// so the cards of a rank land in different suits and no suit gets more than two of seven cards.
+fn spread_suits(counts: &[u8; 13]) -> Deck {
+    let mut deck = 0;
+    let mut suit = 0;
+    for (r, count) in counts.iter().enumerate() {
+        for _ in 0..*count {
+            deck |= 1 << (13 * suit + r);
+            suit = (suit + 1) % 4;
+        }
+    }
+    deck
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::combinations::{binomial, Combinations};
+    use crate::deck::{self, FULL_DECK};
+    use crate::evaluator::{cross_check, random_decks};
+    use std::thread;
+
+    fn reference(deck: Deck) -> Strength {
+        analyser::analyse(deck).strength()
+    }
+
+    #[test]
+    fn test_table_sizes() {
+        let tables = LookupEvaluator::new().tables;
+        assert_eq!(tables.ranks[5].len(), 6175);
+        assert_eq!(tables.ranks[7].len(), 49205);
+        assert!(tables.ranks.iter().flatten().all(|value| *value > 0));
+        assert_eq!(tables.flushes.iter().filter(|value| **value > 0).count() as u64, (5..=13).map(|k| binomial(13, k)).sum::<u64>());
+    }
+
+    #[test]
+    fn test_random_hands() {
+        let lookup = LookupEvaluator::new();
+        for size in 5..=7 {
+            let mut rng = deck::seeded_rng(size as u64);
+            assert_eq!(cross_check(&lookup, &reference, random_decks(&mut rng, size, 50000)), Ok(50000));
+        }
+    }
+
+    // Independent of how the analyser handles more than five cards
+    #[test]
+    fn test_best_of_five_subsets() {
+        let lookup = LookupEvaluator::new();
+        let best_subset = |deck: Deck| Combinations::new(deck, 5).map(reference).max().unwrap();
+        let mut rng = deck::seeded_rng(7);
+        assert_eq!(cross_check(&lookup, &best_subset, random_decks(&mut rng, 7, 20000)), Ok(20000));
+    }
+
+    #[test]
+    fn test_flushes() {
+        let lookup = LookupEvaluator::new();
+        let hearts = deck::to_deck(&["2h", "4h", "7h", "9h", "Jh", "Qh", "Ah"]).unwrap();
+        assert_eq!(lookup.evaluate(hearts), reference(hearts));
+        let straight_flush = deck::to_deck(&["9d", "Td", "Jd", "Qd", "Kd", "Kc", "Ks"]).unwrap();
+        assert_eq!(lookup.evaluate(straight_flush).category(), crate::hand::Category::StraightFlush);
+    }
+
+    #[test]
+    fn test_batch_matches_single_evaluation() {
+        let lookup = LookupEvaluator::new();
+        let mut rng = deck::seeded_rng(22);
+        let decks: Vec<Deck> = (0..1003).map(|i| random_decks(&mut rng, 5 + i % 3, 1).next().unwrap()).collect();
+        let expected: Vec<Strength> = decks.iter().map(|deck| lookup.evaluate(*deck)).collect();
+
+        let mut strengths = vec![Strength::MIN; decks.len()];
+        lookup.evaluate_batch(&decks, &mut strengths);
+        assert_eq!(strengths, expected);
+
+        let mut strengths = vec![Strength::MIN; 3];
+        lookup.evaluate_batch(&decks[..3], &mut strengths);
+        assert_eq!(strengths, expected[..3]);
+        lookup.evaluate_batch(&[], &mut []);
+
+        let mut many = vec![Strength::MAX];
+        Evaluator::evaluate_many(&lookup, &decks, &mut many);
+        assert_eq!(many[0], Strength::MAX);
+        assert_eq!(many[1..], expected);
+    }
+
+    #[test]
+    #[should_panic(expected = "Lookup evaluation needs between 5 and 7 cards, got 4")]
+    fn test_too_few_cards() {
+        LookupEvaluator::new().evaluate(0b1111);
+    }
+
+    // Takes a couple of minutes, run with cargo test --release -- --ignored
+    #[test]
+    #[ignore]
+    fn test_all_seven_card_hands() {
+        let lookup = LookupEvaluator::new();
+        let parts = thread::available_parallelism().map_or(4, |n| n.get());
+        let checked: u64 = thread::scope(|scope| {
+            let workers: Vec<_> = Combinations::new(FULL_DECK, 7)
+                .split(parts)
+                .into_iter()
+                .map(|part| scope.spawn(move || cross_check(&lookup, &reference, part)))
+                .collect();
+            workers.into_iter().map(|worker| worker.join().unwrap().unwrap()).sum()
+        });
+        assert_eq!(checked, 133784560);
+    }
+}
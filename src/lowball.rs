@@ -0,0 +1,247 @@
+use crate::analyser::{self, AnalyseError};
+use crate::combinations::Combinations;
+use crate::deck::{Card, Deck, Rank};
+use crate::hand::{Category, Hand};
+use std::cmp::Ordering;
+use std::fmt::Display;
+
+// Lowball hands compare like every other hand in the crate: the better hand, which is
+// the lower one, compares greater. The best low of a deck is its maximum.
+
+// Ace-to-five low as played in Razz: aces are low, straights and flushes do not count,
+// so 5-4-3-2-A is the best hand. Only pairs and the like count against a hand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AceToFive {
+    category: Category,
+    // From the most to the least significant, pairs before single cards
+    ranks: [Rank; 5],
+}
+
+// Deuce-to-seven low: the worst high hand wins. Aces are high, so A-5-4-3-2 is no straight,
+// and straights and flushes count against a hand, which makes 7-5-4-3-2 the best hand.
+#[derive(Debug, Clone)]
+pub struct DeuceToSeven {
+    // The high hand of the five cards, a straight for A-5-4-3-2
+    hand: Hand,
+    // Lowball value of the hand, where A-5-4-3-2 is ace high or an ace high flush
+    category: Category,
+    ranks: [Rank; 5],
+}
+
+// Value of a rank when aces are low
+pub(crate) fn low_value(rank: Rank) -> u8 {
+    match rank {
+        Rank::Ace => 0,
+        _ => rank.index() + 1,
+    }
+}
+
+impl AceToFive {
+    pub fn category(&self) -> Category {
+        self.category
+    }
+
+    pub fn ranks(&self) -> [Rank; 5] {
+        self.ranks
+    }
+
+    fn from_five(cards: Deck) -> Self {
+        let mut counts = [0u8; 13];
+        for card in (0..52).filter(|i| cards & (1 << i) != 0) {
+            counts[low_value(Card::from_index(card).unwrap().rank()) as usize] += 1;
+        }
+
+        // Larger groups first, higher cards first within groups of the same size
+        let mut groups: Vec<(u8, u8)> = (0..13).filter(|v| counts[*v] > 0).map(|v| (counts[v], v as u8)).collect();
+        groups.sort_unstable_by(|a, b| b.cmp(a));
+
+        let category = match groups.iter().map(|group| group.0).collect::<Vec<_>>()[..] {
+            [1, ..] => Category::HighCard,
+            [2, 1, ..] => Category::Pair,
+            [2, 2, ..] => Category::TwoPairs,
+            [3, 1, ..] => Category::Set,
+            [3, 2] => Category::FullHouse,
+            _ => Category::Quads,
+        };
+
+        let mut ranks = vec![];
+        for (count, value) in groups {
+            let rank = match value {
+                0 => Rank::Ace,
+                _ => Rank::from_index(value - 1).unwrap(),
+            };
+            ranks.extend(std::iter::repeat_n(rank, count as usize));
+        }
+
+        AceToFive { category, ranks: ranks.try_into().unwrap() }
+    }
+}
+
+impl Ord for AceToFive {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let key = |hand: &AceToFive| (hand.category, hand.ranks.map(low_value));
+        key(other).cmp(&key(self))
+    }
+}
+
+impl PartialOrd for AceToFive {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl DeuceToSeven {
+    pub fn hand(&self) -> &Hand {
+        &self.hand
+    }
+
+    pub fn category(&self) -> Category {
+        self.category
+    }
+
+    pub fn ranks(&self) -> [Rank; 5] {
+        self.ranks
+    }
+
+    fn from_five(cards: Deck) -> Self {
+        let hand = analyser::analyse(cards);
+        let mut ranks = hand.ranks();
+        let category = match (hand.category(), ranks[0]) {
+            (Category::Straight, Rank::Five) => Category::HighCard,
+            (Category::StraightFlush, Rank::Five) => Category::Flush,
+            (category, _) => category,
+        };
+        // The wheel runs 5-4-3-2-A as a straight, the ace is its highest card as a low
+        if category != hand.category() {
+            ranks.rotate_right(1);
+        }
+        DeuceToSeven { hand, category, ranks }
+    }
+}
+
+impl Ord for DeuceToSeven {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (other.category, other.ranks).cmp(&(self.category, self.ranks))
+    }
+}
+
+impl PartialEq for DeuceToSeven {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for DeuceToSeven {}
+
+impl PartialOrd for DeuceToSeven {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// "7-5-4-3-2", with the category added when the hand is more than a plain low
+fn write_low(f: &mut std::fmt::Formatter<'_>, ranks: &[Rank; 5], category: Category) -> std::fmt::Result {
+    let ranks: Vec<String> = ranks.iter().map(|rank| rank.to_string()).collect();
+    write!(f, "{}", ranks.join("-"))?;
+    match category {
+        Category::HighCard => Ok(()),
+        _ => write!(f, " ({})", category),
+    }
+}
+
+impl Display for AceToFive {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_low(f, &self.ranks, self.category)
+    }
+}
+
+impl Display for DeuceToSeven {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_low(f, &self.ranks, self.category)
+    }
+}
+
+// Best ace-to-five low out of a deck of at least five cards, e.g. the seven cards of Razz
+pub fn ace_to_five(deck: Deck) -> AceToFive {
+    try_ace_to_five(deck).unwrap_or_else(|error| panic!("{}", error))
+}
+
+pub fn try_ace_to_five(deck: Deck) -> Result<AceToFive, AnalyseError> {
+    best_five(deck, AceToFive::from_five)
+}
+
+// Best deuce-to-seven low out of a deck of at least five cards
+pub fn deuce_to_seven(deck: Deck) -> DeuceToSeven {
+    try_deuce_to_seven(deck).unwrap_or_else(|error| panic!("{}", error))
+}
+
+pub fn try_deuce_to_seven(deck: Deck) -> Result<DeuceToSeven, AnalyseError> {
+    best_five(deck, DeuceToSeven::from_five)
+}
+
+fn best_five<T: Ord, F: Fn(Deck) -> T>(deck: Deck, evaluate: F) -> Result<T, AnalyseError> {
+    // The analyser performs the same checks on the size and content of the deck
+    analyser::try_analyse(deck)?;
+    Ok(Combinations::new(deck, 5).map(evaluate).max().unwrap())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::deck::cards;
+
+    #[test]
+    fn test_ace_to_five() {
+        let wheel = ace_to_five(cards(&["5h", "4h", "3h", "2h", "Ah"]));
+        assert_eq!(wheel.to_string(), "5-4-3-2-A");
+        assert_eq!(wheel.category(), Category::HighCard);
+
+        let razz = ace_to_five(cards(&["Kd", "7c", "5s", "7h", "4d", "3c", "2s"]));
+        assert_eq!(razz.to_string(), "7-5-4-3-2");
+        assert!(wheel > razz);
+
+        let paired = ace_to_five(cards(&["Kd", "Kc", "Ks", "Ah", "Ad", "2c", "2s"]));
+        assert_eq!(paired.to_string(), "2-2-A-A-K (Two Pair)");
+        assert!(paired < ace_to_five(cards(&["Kd", "Qc", "Js", "Th", "9d"])));
+        assert!(ace_to_five(cards(&["6d", "5c", "4s", "3h", "2d"])) > razz);
+    }
+
+    #[test]
+    fn test_deuce_to_seven() {
+        let nuts = deuce_to_seven(cards(&["7d", "5c", "4s", "3h", "2d"]));
+        assert_eq!(nuts.to_string(), "7-5-4-3-2");
+
+        let wheel = deuce_to_seven(cards(&["5h", "4d", "3h", "2h", "Ah"]));
+        assert_eq!(wheel.to_string(), "A-5-4-3-2");
+        assert_eq!(wheel.category(), Category::HighCard);
+        assert_eq!(*wheel.hand(), Hand::straight(Rank::Five));
+        assert!(nuts > wheel);
+
+        let straight = deuce_to_seven(cards(&["7d", "6c", "5s", "4h", "3d"]));
+        assert_eq!(straight.to_string(), "7-6-5-4-3 (Straight)");
+        assert!(straight < deuce_to_seven(cards(&["Kd", "Kc", "5s", "4h", "3d"])));
+
+        let flush = deuce_to_seven(cards(&["7h", "5h", "4h", "3h", "2h"]));
+        assert_eq!(flush.to_string(), "7-5-4-3-2 (Flush)");
+        assert!(flush < wheel);
+        assert_eq!(deuce_to_seven(cards(&["7h", "5h", "4h", "3h", "2h", "8c"])).to_string(), "8-5-4-3-2");
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_deuce_to_seven_wheel_round_trip() {
+        for deck in [cards(&["5h", "4d", "3h", "2h", "Ah"]), cards(&["5h", "4h", "3h", "2h", "Ah"])] {
+            let wheel = deuce_to_seven(deck);
+            let hand = wheel.hand();
+            assert_eq!(Hand::from_strength(hand.strength()), *hand);
+            let json = serde_json::to_string(hand).unwrap();
+            assert_eq!(serde_json::from_str::<Hand>(&json).unwrap(), *hand);
+        }
+    }
+
+    #[test]
+    fn test_not_enough_cards() {
+        assert_eq!(try_ace_to_five(cards(&["5h", "4h", "3h", "2h"])), Err(AnalyseError::NotEnoughCards(4)));
+        assert_eq!(try_deuce_to_seven(0), Err(AnalyseError::NotEnoughCards(0)));
+    }
+}
@@ -1,38 +1,49 @@
-mod hand;
-mod deck;
-mod analyser;
-mod predictor;
-
+use poker_rust::{deck, predictor};
 use std::io::Write;
 
-fn main() {
-    print!("Enter number of opponents: ");
+fn read_line(prompt: &str) -> String {
+    print!("{}", prompt);
     std::io::stdout().flush().unwrap();
     let mut buf = String::new();
-    std::io::stdin().read_line(&mut buf).unwrap();
-    println!("{}", buf);
-    let opponents: u8 = buf.trim().parse().unwrap();
-    print!("Enter your cards: ");
-    std::io::stdout().flush().unwrap();
-    buf.clear();
-    std::io::stdin().read_line(&mut buf).unwrap();
-    let mut it = buf.split(' ');
-    let deck: deck::Deck = deck::to_deck(&[it.next().unwrap(), it.next().unwrap()]);
-    let mut players = vec![deck];
+    if std::io::stdin().read_line(&mut buf).unwrap() == 0 {
+        std::process::exit(1);
+    }
+    buf
+}
+
+fn read_cards(prompt: &str, dealt: deck::Deck) -> deck::Deck {
+    loop {
+        let buf = read_line(prompt);
+        match deck::parse_cards(&buf, deck::Notation::Lenient) {
+            Ok(hand) if hand.count_ones() != 2 => println!("Expected 2 cards, got {}", hand.count_ones()),
+            Ok(hand) if hand & dealt != 0 => println!("Cards {:?} have already been dealt", deck::from_deck(hand & dealt)),
+            Ok(hand) => return hand,
+            Err(error) => println!("Invalid cards: {}", error),
+        }
+    }
+}
+
+fn main() {
+    let opponents: u8 = loop {
+        match read_line("Enter number of opponents: ").trim().parse() {
+            Ok(opponents) => break opponents,
+            Err(error) => println!("Invalid number of opponents: {}", error),
+        }
+    };
+
+    let mut dealt = read_cards("Enter your cards: ", 0);
+    let mut players = vec![dealt];
     
     for _ in 0..opponents {
-        buf.clear();
-        print!("Enter opponent's cards: ");
-        std::io::stdout().flush().unwrap();
-        std::io::stdin().read_line(&mut buf).unwrap();
-        let mut it = buf.split(' ');
-        let deck = deck::to_deck(&[it.next().unwrap(), it.next().unwrap()]);
+        let deck = read_cards("Enter opponent's cards: ", dealt);
+        dealt |= deck;
         players.push(deck);
     }
 
-    let odds = predictor::predict(&players);
+    let equity = predictor::equity(&players);
+    println!("Found {} distinct combinations of boards", equity.boards);
+    let odds: Vec<f32> = equity.players.iter().map(|player| player.wins as f32 / equity.boards as f32).collect();
     println!("Winning odds {:.2}", odds[0] * 100f32);
     println!("Opponent odds {:.2}", odds[1..].iter().sum::<f32>() * 100f32);
     println!("Tie odds {:.2}", 100f32 - odds.iter().sum::<f32>() * 100f32);
 }
-
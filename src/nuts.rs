@@ -0,0 +1,138 @@
+use crate::analyser;
+use crate::combinations::Combinations;
+use crate::deck::{check_hole_and_board, CardSet, Deck, HoleBoardError, FULL_DECK};
+use crate::hand::Hand;
+use std::fmt::Display;
+
+// Where a Hold'em hand stands among every two cards an opponent can hold.
+// Holdings that use one of hero's cards are impossible and left out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NutRanking {
+    pub hand: Hand,
+    // 1 for the nuts, 2 when a single stronger hand exists however many holdings make it
+    pub position: usize,
+    // Number of holdings the opponent can have
+    pub holdings: usize,
+    // Number of holdings hero beats
+    pub beaten: usize,
+    // Holdings that beat hero, the strongest first
+    pub better: Vec<CardSet>,
+    pub tied: Vec<CardSet>,
+}
+
+impl NutRanking {
+    pub fn is_nuts(&self) -> bool {
+        self.position == 1
+    }
+
+    // Share of the holdings hero beats, from 0 to 1
+    pub fn beats(&self) -> f64 {
+        self.beaten as f64 / self.holdings as f64
+    }
+}
+
+// "the nuts", "2nd nuts", "11th nuts"
+fn position_name(position: usize) -> String {
+    let suffix = match (position % 10, position % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    match position {
+        1 => "the nuts".to_string(),
+        _ => format!("{}{} nuts", position, suffix),
+    }
+}
+
+// "2nd nuts, beats 87% of holdings"
+impl Display for NutRanking {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}, beats {:.0}% of holdings", position_name(self.position), self.beats() * 100.0)
+    }
+}
+
+// Ranks hero's hand against all two card holdings that the board and hero's own cards leave possible.
+// Hands are ranked from the flop on.
+pub fn nut_ranking(hole: Deck, board: Deck) -> Result<NutRanking, HoleBoardError> {
+    check_hole_and_board(hole, 2, board, 3..=5)?;
+
+    let hand = analyser::analyse(hole | board);
+    let strength = hand.strength();
+    let mut better = vec![];
+    let mut tied = vec![];
+    let mut holdings = 0;
+    for holding in Combinations::new(FULL_DECK & !(hole | board), 2) {
+        holdings += 1;
+        let other = analyser::analyse(holding | board).strength();
+        if other > strength {
+            better.push((other, holding));
+        } else if other == strength {
+            tied.push(CardSet::from_deck(holding));
+        }
+    }
+
+    better.sort_unstable_by_key(|(other, _)| std::cmp::Reverse(*other));
+    let mut stronger: Vec<_> = better.iter().map(|(other, _)| *other).collect();
+    stronger.dedup();
+
+    Ok(NutRanking {
+        hand,
+        position: stronger.len() + 1,
+        holdings,
+        beaten: holdings - better.len() - tied.len(),
+        better: better.into_iter().map(|(_, holding)| CardSet::from_deck(holding)).collect(),
+        tied,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::deck::cards;
+
+    #[test]
+    fn test_nuts() {
+        let ranking = nut_ranking(cards(&["Jh", "Th"]), cards(&["Ah", "Kh", "Qh", "7c", "2d"])).unwrap();
+        assert!(ranking.is_nuts());
+        assert_eq!(ranking.holdings, 990);
+        assert_eq!(ranking.beaten, 990);
+        assert_eq!(ranking.to_string(), "the nuts, beats 100% of holdings");
+    }
+
+    #[test]
+    fn test_stronger_holdings() {
+        // Every king of hearts with another heart makes a better flush
+        let board = cards(&["Ah", "9h", "7h", "4c", "2d"]);
+        let ranking = nut_ranking(cards(&["Qh", "Jh"]), board).unwrap();
+        assert_eq!(ranking.position, 8);
+        assert_eq!(ranking.better.len(), 7);
+        assert_eq!(ranking.better[0], CardSet::from_deck(cards(&["Kh", "Th"])));
+        assert!(ranking.tied.is_empty());
+        assert!(ranking.to_string().starts_with("8th nuts, beats 9"));
+
+        // Holding the king of hearts blocks all of them
+        let ranking = nut_ranking(cards(&["Kh", "Jh"]), board).unwrap();
+        assert!(ranking.is_nuts());
+        assert!(ranking.better.is_empty());
+    }
+
+    #[test]
+    fn test_ties() {
+        // Everybody plays the straight on the board
+        let ranking = nut_ranking(cards(&["2c", "3d"]), cards(&["As", "Ks", "Qd", "Jc", "Tc"])).unwrap();
+        assert!(ranking.is_nuts());
+        assert_eq!(ranking.tied.len(), 990);
+        assert_eq!(ranking.beats(), 0.0);
+        assert_eq!(ranking.to_string(), "the nuts, beats 0% of holdings");
+    }
+
+    #[test]
+    fn test_position_names() {
+        assert_eq!(position_name(2), "2nd nuts");
+        assert_eq!(position_name(3), "3rd nuts");
+        assert_eq!(position_name(11), "11th nuts");
+        assert_eq!(position_name(21), "21st nuts");
+    }
+}
@@ -0,0 +1,92 @@
+use crate::analyser;
+use crate::combinations::Combinations;
+use crate::deck::{check_hole_and_board, Deck, HoleBoardError};
+use crate::hand::Hand;
+use crate::lookup::LookupEvaluator;
+
+// Omaha games by the number of hole cards every player gets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Omaha {
+    #[default]
+    Plo4,
+    Plo5,
+    Plo6,
+}
+
+impl Omaha {
+    pub fn hole_cards(self) -> usize {
+        match self {
+            Omaha::Plo4 => 4,
+            Omaha::Plo5 => 5,
+            Omaha::Plo6 => 6,
+        }
+    }
+
+    pub fn from_hole_cards(count: usize) -> Option<Omaha> {
+        match count {
+            4 => Some(Omaha::Plo4),
+            5 => Some(Omaha::Plo5),
+            6 => Some(Omaha::Plo6),
+            _ => None,
+        }
+    }
+}
+
+// Best hand made of exactly two hole cards and exactly three board cards, on the flop, turn or river
+pub fn analyse(game: Omaha, hole: Deck, board: Deck) -> Result<Hand, HoleBoardError> {
+    check_hole_and_board(hole, game.hole_cards(), board, 3..=5)?;
+
+    // The tables find the best combination, the analyser then names it with its actual suit
+    let lookup = LookupEvaluator::new();
+    let best = Combinations::new(hole, 2)
+        .flat_map(|two| Combinations::new(board, 3).map(move |three| two | three))
+        .max_by_key(|cards| lookup.evaluate(*cards))
+        .unwrap();
+    Ok(analyser::analyse(best))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::deck::{cards, Rank::*, Suit};
+
+    #[test]
+    fn test_two_hole_cards_needed() {
+        // Four spades on the board and only one in the hole is no flush
+        let board = cards(&["2s", "7s", "9s", "Js", "Kd"]);
+        let hole = cards(&["As", "Ah", "Qc", "3d"]);
+        assert_eq!(analyse(Omaha::Plo4, hole, board), Ok(Hand::pairs(&[Ace], &[King, Jack, Nine])));
+        assert_eq!(analyser::analyse(hole | board).category(), crate::hand::Category::Flush);
+    }
+
+    #[test]
+    fn test_three_board_cards_needed() {
+        // Four of a kind in the hole only plays as a pair
+        let board = cards(&["2s", "7h", "9c"]);
+        let hole = cards(&["Ks", "Kh", "Kc", "Kd"]);
+        assert_eq!(analyse(Omaha::Plo4, hole, board), Ok(Hand::pairs(&[King], &[Nine, Seven, Two])));
+
+        // A straight on the board does not play at all
+        let board = cards(&["5s", "6h", "7c", "8d", "9s"]);
+        let hole = cards(&["Ac", "Ad", "2c", "2h"]);
+        assert_eq!(analyse(Omaha::Plo4, hole, board), Ok(Hand::pairs(&[Ace], &[Nine, Eight, Seven])));
+    }
+
+    #[test]
+    fn test_larger_games() {
+        let hole = cards(&["Ah", "Kh", "4c", "5c", "6d"]);
+        assert_eq!(analyse(Omaha::Plo5, hole, cards(&["Th", "Jh", "Qd", "2c", "3s"])), Ok(Hand::straight(Ace)));
+        let board = cards(&["Th", "Jh", "Qd", "2c", "3h"]);
+        let hole = cards(&["Ah", "9h", "4c", "5c", "6d", "Kc"]);
+        assert_eq!(analyse(Omaha::Plo6, hole, board), Ok(Hand::flush(&[Ace, Jack, Ten, Nine, Three], Suit::Hearts)));
+        assert_eq!(analyse(Omaha::Plo6, hole, cards(&["Th", "Jh", "Qd"])), Ok(Hand::straight(Ace)));
+    }
+
+    #[test]
+    fn test_hole_cards_of_the_game() {
+        let hole = cards(&["Ah", "Kh", "4c", "5c"]);
+        let error = HoleBoardError::WrongHoleCards { expected: 5, got: 4 };
+        assert_eq!(analyse(Omaha::Plo5, hole, cards(&["Th", "Jh", "Qd"])), Err(error));
+        assert_eq!(Omaha::from_hole_cards(6), Some(Omaha::Plo6));
+    }
+}
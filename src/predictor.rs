@@ -1,148 +1,429 @@
-use crate::deck::Deck;
-use crate::analyser;
+use crate::combinations::Combinations;
+use crate::deck::{CardSet, Deck, FULL_DECK};
+use crate::evaluator::{Analyser, Evaluator};
+use crate::hilo::HiLo;
+use crate::lookup::LookupEvaluator;
+use crate::variant::Variant;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 
-pub fn predict(players: &[Deck]) -> Vec<f32> {
-    let mut deck = 0;
+// Outcome of running every possible board for a set of players
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Equity {
+    pub boards: u64,
+    pub players: Vec<PlayerEquity>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PlayerEquity {
+    pub hand: CardSet,
+    // Boards won outright
+    pub wins: u64,
+    // Boards where the pot is split with at least one other player
+    pub ties: u64,
+    // Average share of the pot, split pots count as a fraction
+    pub equity: f64,
+}
 
-    for player in players {
-        assert_eq!(deck & player, 0, "Detected card that has been dealt more than once");
-        deck += player;
+impl PlayerEquity {
+    pub fn win_rate(&self, boards: u64) -> f64 {
+        self.wins as f64 / boards as f64
     }
 
-    let combinations = find_all_combinations(deck, 5);
-    println!("Found {} distinct combinations of boards", combinations.len());
-    let odds = compare_player_hands(players, &combinations);
-    odds
+    pub fn tie_rate(&self, boards: u64) -> f64 {
+        self.ties as f64 / boards as f64
+    }
 }
 
-fn find_all_combinations(mut deck: Deck, k: u32) -> Vec<Deck> {
-    let n = deck.count_zeros() - 12;
-    assert!(n >= k);
-    let combination_num = calculate_combination_num(n, k as u32);
-    let mut combinations = vec![];
-    combinations.reserve(combination_num as usize);
-    find_next_combination(&mut combinations, &mut deck, 52 - n + k, 0);
-    return combinations;
+// Split pot results, where a pot without a qualifying low goes to the high hand alone
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HiLoEquity {
+    pub boards: u64,
+    pub players: Vec<HiLoPlayerEquity>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HiLoPlayerEquity {
+    pub hand: CardSet,
+    // Boards where the player takes the high half, or the whole pot without a low, alone
+    pub high_wins: u64,
+    // Boards where the player takes the low half alone
+    pub low_wins: u64,
+    // Boards where the player takes the whole pot alone
+    pub scoops: u64,
+    // Boards where the player ends up with a quarter of the pot
+    pub quartered: u64,
+    // Expected fraction of the pot
+    pub equity: f64,
+}
 
-fn calculate_combination_num(n: u32, k: u32) -> u64 {
-    assert!(n >= k);
-    let factorial = |begin: u32, end: u32| {
-        let mut f: u64 = 1;
-        
-        for i in begin..end+1 {
-            f *= i as u64;
-        }
+pub fn predict(players: &[Deck]) -> Vec<f32> {
+    predict_variant(players, Variant::Holdem)
+}
 
-        f
-    };
+// Fraction of the boards every player wins outright
+pub fn predict_variant(players: &[Deck], variant: Variant) -> Vec<f32> {
+    let equity = equity_variant(players, variant);
+    equity.players.iter().map(|player| {
+        player.wins as f32 / equity.boards as f32
+    }).collect()
+}
 
-    return factorial(n - k + 1, n) / factorial(1, k);
+pub fn equity(players: &[Deck]) -> Equity {
+    equity_variant(players, Variant::Holdem)
 }
 
-fn find_next_combination(combinations: &mut Vec<u64>, deck: &mut u64, hand_size: u32, index: u32) {
-    if deck.count_ones() == hand_size {
-        combinations.push(*deck);
-        return;
+// Hold'em hands go through the lookup tables, the other variants through the analyser.
+// The tables take at most seven cards, so players with more than two cards need the analyser too.
+pub fn equity_variant(players: &[Deck], variant: Variant) -> Equity {
+    match variant {
+        Variant::Holdem if players.iter().all(|player| player.count_ones() <= 2) => {
+            equity_with(players, variant, &LookupEvaluator::new())
+        },
+        _ => equity_with(players, variant, &Analyser::new(variant)),
     }
+}
 
-    for i in index..52 {
-        if *deck & ((0x1 as u64) << i) == 0 {
-            *deck |= 0x1 << i;
-            find_next_combination(combinations, deck, hand_size, i + 1);
-            *deck -= 0x1 << i;
-        }
+// Boards are dealt from the deck of the variant and every player is evaluated with the evaluator
+pub fn equity_with<E: Evaluator>(players: &[Deck], variant: Variant, evaluator: &E) -> Equity {
+    let mut deck = CardSet::EMPTY;
+
+    for player in players.iter().map(|player| CardSet::from_deck(*player)) {
+        assert!(deck.is_disjoint(player), "Detected card that has been dealt more than once");
+        assert!(player.is_subset(variant.deck()), "Detected card that is not part of the {:?} deck", variant);
+        deck |= player;
     }
+
+    // Dead cards are marked as dealt so that they never show up on a board
+    let combinations = find_all_combinations((deck | variant.dead_cards()).to_deck(), 5);
+    compare_player_hands(players, combinations, evaluator)
 }
 
-fn compare_player_hands(players: &[Deck], combinations: &[Deck]) -> Vec<f32> {
-    let mut player_odds = vec![0; players.len()];
-    let all_players: Deck = players.iter().sum();
-
-    for combination in combinations.iter().map(|c| c - all_players) {
-        let mut hands = vec![analyser::analyse(combination + players[0])];
-        hands.reserve(players.len());
-        let mut winner_index = 0;
-        let mut winners = vec![winner_index];
-
-        for i in 1..players.len() {
-            let combination = combination + players[i];
-            hands.push(analyser::analyse(combination));
-            match hands[winner_index].partial_cmp(&hands[hands.len() - 1]) {
-                Some(std::cmp::Ordering::Equal) => winners.push(i),
-                Some(std::cmp::Ordering::Greater) => {},
-                Some(std::cmp::Ordering::Less) => {
-                    winner_index = i;
-                    winners.clear();
-                    winners.push(i);
-                }
-                None => panic!(),
+// Deals the rest of the board in every possible way and splits each pot between the best high
+// and the best low hand. evaluate receives the hole cards of a player and the complete board.
+pub fn hilo_equity<F: Fn(Deck, Deck) -> HiLo>(players: &[Deck], board: Deck, evaluate: F) -> HiLoEquity {
+    let mut deck = CardSet::from_deck(board);
+    assert!(deck.len() <= 5, "The board cannot have more than 5 cards");
+
+    for player in players.iter().map(|player| CardSet::from_deck(*player)) {
+        assert!(deck.is_disjoint(player), "Detected card that has been dealt more than once");
+        deck |= player;
+    }
+
+    let mut shares = vec![0f64; players.len()];
+    let mut equity: Vec<HiLoPlayerEquity> = players.iter().map(|player| HiLoPlayerEquity {
+        hand: CardSet::from_deck(*player),
+        high_wins: 0,
+        low_wins: 0,
+        scoops: 0,
+        quartered: 0,
+        equity: 0.0,
+    }).collect();
+
+    let boards = find_all_combinations(deck.to_deck(), 5 - board.count_ones());
+    let board_count = boards.len() as u64;
+    for rest in boards {
+        let hands: Vec<HiLo> = players.iter().map(|player| evaluate(*player, board | rest)).collect();
+        let high = hands.iter().map(|hand| &hand.high).max().unwrap();
+        let high_winners: Vec<usize> = (0..hands.len()).filter(|i| hands[*i].high == *high).collect();
+        let low = hands.iter().filter_map(|hand| hand.low).max();
+        let low_winners: Vec<usize> = (0..hands.len()).filter(|i| low.is_some() && hands[*i].low == low).collect();
+
+        // Both halves go to the high hands when nobody has a low
+        let high_pot = match low {
+            Some(_) => 0.5,
+            None => 1.0,
+        };
+        let mut board_shares = vec![0f64; players.len()];
+        for winner in high_winners.iter() {
+            board_shares[*winner] += high_pot / high_winners.len() as f64;
+        }
+        for winner in low_winners.iter() {
+            board_shares[*winner] += 0.5 / low_winners.len() as f64;
+        }
+
+        if let [winner] = high_winners[..] {
+            equity[winner].high_wins += 1;
+        }
+        if let [winner] = low_winners[..] {
+            equity[winner].low_wins += 1;
+        }
+        for (i, share) in board_shares.into_iter().enumerate() {
+            // A scoop takes both halves alone, a quarter splits one half with one other player
+            // and gets nothing from the other half
+            let (in_high, in_low) = (high_winners.contains(&i), low_winners.contains(&i));
+            if high_winners == [i] && (low.is_none() || low_winners == [i]) {
+                equity[i].scoops += 1;
+            }
+            let quartered = match (in_high, in_low) {
+                (true, false) => low.is_some() && high_winners.len() == 2,
+                (false, true) => low_winners.len() == 2,
+                _ => false,
+            };
+            if quartered {
+                equity[i].quartered += 1;
             }
+            shares[i] += share;
+        }
+    }
+
+    for (player, share) in equity.iter_mut().zip(shares) {
+        player.equity = share / board_count as f64;
+    }
+    HiLoEquity { boards: board_count, players: equity }
+}
+
+// Lazily enumerates every k card board that can be drawn from the cards missing from deck
+fn find_all_combinations(deck: Deck, k: u32) -> Combinations {
+    let n = deck.count_zeros() - 12;
+    assert!(n >= k);
+    Combinations::new(!deck & FULL_DECK, k)
+}
+
+// Boards are evaluated in groups so that batching evaluators see many decks at once
+const BOARDS_PER_BATCH: usize = 256;
+
+fn compare_player_hands<E: Evaluator, I: Iterator<Item = Deck>>(players: &[Deck], mut boards: I, evaluator: &E) -> Equity {
+    let mut board_count = 0;
+    let mut shares = vec![0f64; players.len()];
+    let mut equity: Vec<PlayerEquity> = players.iter().map(|player| PlayerEquity {
+        hand: CardSet::from_deck(*player),
+        wins: 0,
+        ties: 0,
+        equity: 0.0,
+    }).collect();
+
+    let mut decks = Vec::with_capacity(BOARDS_PER_BATCH * players.len());
+    let mut strengths = Vec::with_capacity(BOARDS_PER_BATCH * players.len());
+    loop {
+        decks.clear();
+        strengths.clear();
+        for board in boards.by_ref().take(BOARDS_PER_BATCH) {
+            board_count += 1;
+            decks.extend(players.iter().map(|player| board | player));
+        }
+        if decks.is_empty() {
+            break;
         }
+        evaluator.evaluate_many(&decks, &mut strengths);
 
-        if winners.len() == 1 {
-            player_odds[winners[0]] += 1;
+        for hands in strengths.chunks(players.len()) {
+            let best = hands.iter().max().unwrap();
+            let winners: Vec<usize> = (0..hands.len()).filter(|i| hands[*i].cmp(best) == Ordering::Equal).collect();
+
+            for winner in winners.iter() {
+                match winners.len() {
+                    1 => equity[*winner].wins += 1,
+                    _ => equity[*winner].ties += 1,
+                }
+                shares[*winner] += 1.0 / winners.len() as f64;
+            }
         }
     }
 
-    player_odds.iter().map(|i| {
-        *i as f32 / combinations.len() as f32
-    }).collect()
+    for (player, share) in equity.iter_mut().zip(shares) {
+        player.equity = share / board_count as f64;
+    }
+    Equity { boards: board_count, players: equity }
 }
 
 #[cfg(test)]
 mod test {
     use super::{*};
+    use crate::combinations::binomial;
     use crate::deck;
+    use crate::analyser;
 
     #[test]
     fn test_n50_k5() {
-        let combinations: Vec<u64> = find_all_combinations(0b110, 5);
-        assert_eq!(combinations.len() as u64, calculate_combination_num(52 - 2, 5));
+        let combinations = find_all_combinations(0b110, 5);
+        assert_eq!(combinations.len() as u64, binomial(52 - 2, 5));
     }
 
     #[test]
     #[should_panic(expected = "assertion failed: n >= k")]
+    #[allow(unused_variables)]
     fn test_empty_deck() {
-        let combinations: Vec<u64> = find_all_combinations(0xfffffffffffff, 1);
+        let combinations = find_all_combinations(0xfffffffffffff, 1);
     }
 
     #[test]
     #[should_panic(expected = "attempt to subtract with overflow")]
+    #[allow(unused_variables)]
     fn test_overflown_deck() {
-        let combinations: Vec<u64> = find_all_combinations(0x1fffffffffffff, 0);
+        let combinations = find_all_combinations(0x1fffffffffffff, 0);
     }
 
     #[test]
     fn test_n46_k1() {
         let combinations = find_all_combinations(0b1111110, 1);
-        assert_eq!(combinations.len() as u64, calculate_combination_num(52 - 6, 1));
+        assert_eq!(combinations.len() as u64, binomial(52 - 6, 1));
     }
 
     #[test]
+    #[allow(non_snake_case, clippy::useless_vec)]
     fn test_AKsuited_vs_72suited() {
         let players = &vec![0b100001, 0b1100000000000];
         let combinations = find_all_combinations(players[0] + players[1], 5);
-        assert_eq!(combinations.len() as u64, calculate_combination_num(48, 5));
-        let odds = compare_player_hands(players, &combinations);
+        assert_eq!(combinations.len() as u64, binomial(48, 5));
+        let equity = compare_player_hands(players, combinations, &Analyser::default());
+        let odds: Vec<f32> = equity.players.iter().map(|player| player.win_rate(equity.boards) as f32).collect();
         let tie_odds = 1f32 - odds[0] - odds[1];
-        assert_eq!((tie_odds * 100000f32).trunc() as i32, 637);
+        assert_eq!((tie_odds * 100000f32).trunc() as i32, 615);
+
+        assert_eq!(equity.boards, binomial(48, 5));
+        assert_eq!(equity.players[0].ties, equity.players[1].ties);
+        assert_eq!(equity.players.iter().map(|player| player.wins).sum::<u64>() + equity.players[0].ties, equity.boards);
+        assert!((equity.players[0].equity + equity.players[1].equity - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_evaluators_agree() {
+        let players = &[deck::to_deck(&["Qs", "Js"]).unwrap(), deck::to_deck(&["4h", "4d"]).unwrap()];
+        let boards = || find_all_combinations(players[0] | players[1], 5).take(20000);
+        let strength = |deck| analyser::analyse(deck).strength();
+        assert_eq!(
+            compare_player_hands(players, boards(), &Analyser::default()),
+            compare_player_hands(players, boards(), &strength)
+        );
+    }
+
+    #[test]
+    fn test_more_than_two_hole_cards() {
+        // Nine cards per player are too many for the lookup tables, the royal flush never loses
+        let players = &[
+            deck::to_deck(&["Ah", "Kh", "Qh", "Jh", "Th", "2c", "2d", "2s", "3c"]).unwrap(),
+            deck::to_deck(&["As", "Ad", "Ac", "Ks", "Kd", "Kc", "Qs", "Qd", "Qc"]).unwrap(),
+            deck::to_deck(&["Js", "Jd", "Jc", "Ts", "Td", "Tc", "9s", "9d", "9c"]).unwrap(),
+            deck::to_deck(&["8s", "8d", "8c", "7s", "7d", "7c", "6s", "6d", "6c"]).unwrap(),
+        ];
+        let equity = equity(players);
+        assert_eq!(equity.boards, binomial(16, 5));
+        assert_eq!(equity.players[0].wins + equity.players[0].ties, equity.boards);
     }
 
     #[test]
     fn test_6_way_hand() {
         let players = vec![
-            deck::to_deck(&["Ac", "Ad"]), 
-            deck::to_deck(&["7d", "2c"]),
-            deck::to_deck(&["7c", "2d"]),
-            deck::to_deck(&["8c", "2h"]),
-            deck::to_deck(&["Ks", "Qs"]),
-            deck::to_deck(&["Th", "9s"]),
+            deck::to_deck(&["Ac", "Ad"]).unwrap(), 
+            deck::to_deck(&["7d", "2c"]).unwrap(),
+            deck::to_deck(&["7c", "2d"]).unwrap(),
+            deck::to_deck(&["8c", "2h"]).unwrap(),
+            deck::to_deck(&["Ks", "Qs"]).unwrap(),
+            deck::to_deck(&["Th", "9s"]).unwrap(),
         ];
         let odds = predict(&players);
-        assert_eq!((odds[0] * 10000f32).trunc() as i32, 5609);
-        assert_eq!((odds[1..].iter().sum::<f32>() * 10000f32).trunc() as i32, 3952);
-        assert_eq!(((1f32 - odds.iter().sum::<f32>()) * 10000f32).trunc() as i32, 437);
+        assert_eq!((odds[0] * 10000f32).trunc() as i32, 5617);
+        assert_eq!((odds[1..].iter().sum::<f32>() * 10000f32).trunc() as i32, 3961);
+        assert_eq!(((1f32 - odds.iter().sum::<f32>()) * 10000f32).trunc() as i32, 420);
+    }
+
+    #[test]
+    fn test_short_deck_heads_up() {
+        let players = vec![
+            deck::to_deck(&["Ah", "Kh"]).unwrap(),
+            deck::to_deck(&["7c", "7d"]).unwrap(),
+        ];
+        let dead = (Variant::ShortDeck.dead_cards() | CardSet::from_deck(players[0] | players[1])).to_deck();
+        assert_eq!(find_all_combinations(dead, 5).len() as u64, binomial(32, 5));
+
+        let odds = predict_variant(&players, Variant::ShortDeck);
+        assert!(odds[0] > odds[1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Detected card that is not part of the ShortDeck deck")]
+    fn test_short_deck_rejects_low_cards() {
+        predict_variant(&[deck::to_deck(&["Ah", "5h"]).unwrap()], Variant::ShortDeck);
+    }
+
+    #[test]
+    fn test_omaha_hilo_equity() {
+        use crate::hilo;
+        use crate::omaha::Omaha;
+
+        let omaha = |hole, board| hilo::omaha(Omaha::Plo4, hole, board).unwrap();
+        let board = deck::to_deck(&["2c", "4d", "Kh"]).unwrap();
+        let players = &[
+            deck::to_deck(&["Ah", "3c", "Kc", "Ks"]).unwrap(),
+            deck::to_deck(&["Ad", "3s", "Qc", "Jd"]).unwrap(),
+            deck::to_deck(&["Th", "9h", "Tc", "9s"]).unwrap(),
+        ];
+        let equity = hilo_equity(players, board, omaha);
+        assert_eq!(equity.boards, binomial(52 - 15, 2));
+
+        // Both lows are the same, so every low is split and the first player never scoops one
+        assert_eq!(equity.players[0].low_wins, 0);
+        assert_eq!(equity.players[1].low_wins, 0);
+        assert!(equity.players[0].quartered > 0);
+        assert_eq!(equity.players[2].quartered, 0);
+        assert!(equity.players[0].scoops > 0);
+        assert!(equity.players[0].equity > equity.players[1].equity);
+        assert!((equity.players.iter().map(|player| player.equity).sum::<f64>() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_hilo_scoops_and_quarters() {
+        use crate::deck::{cards, Rank::*};
+        use crate::hand::Hand;
+        use crate::lowball::ace_to_five;
+
+        // A complete board, so there is a single deal and the hands only depend on the hole cards
+        let board = cards(&["2c", "3d", "4h", "9s", "Kc"]);
+        let (a, b, c) = (cards(&["Ah", "Ad"]), cards(&["As", "Ac"]), cards(&["7h", "8h"]));
+        let wheel = ace_to_five(cards(&["5h", "4h", "3h", "2h", "Ah"]));
+        let straight = Hand::straight(Ace);
+        let king_high = Hand::highcard(&[King, Nine, Four, Three, Two]);
+        let outcome = |high: &[u64], low: &[u64]| hilo_equity(&[a, b, c], board, |hole, _| HiLo {
+            high: if high.contains(&hole) { straight.clone() } else { king_high.clone() },
+            low: low.contains(&hole).then_some(wheel),
+        });
+        let counts = |equity: &HiLoEquity| -> Vec<(u64, u64)> {
+            equity.players.iter().map(|player| (player.scoops, player.quartered)).collect()
+        };
+
+        // Splitting the high and taking the low alone is three quarters, neither a scoop nor a quarter
+        let equity = outcome(&[a, b], &[a]);
+        assert_eq!(counts(&equity), [(0, 0), (0, 1), (0, 0)]);
+        assert_eq!(equity.players[0].equity, 0.75);
+
+        let equity = outcome(&[a], &[b, c]);
+        assert_eq!(counts(&equity), [(0, 0), (0, 1), (0, 1)]);
+
+        // Without a low the high hand takes the whole pot
+        assert_eq!(counts(&outcome(&[a], &[])), [(1, 0), (0, 0), (0, 0)]);
+        assert_eq!(counts(&outcome(&[a, b], &[])), [(0, 0), (0, 0), (0, 0)]);
+        assert_eq!(counts(&outcome(&[c], &[c])), [(0, 0), (0, 0), (1, 0)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Detected card that has been dealt more than once")]
+    fn test_hilo_equity_rejects_duplicates() {
+        let player = deck::to_deck(&["Ah", "3c", "Kc", "Ks"]).unwrap();
+        hilo_equity(&[player], deck::to_deck(&["Ah", "4d", "Kh"]).unwrap(), |hole, board| crate::hilo::stud(hole | board).unwrap());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_equity_serde_round_trip() {
+        let players = &[deck::to_deck(&["As", "Ks"]).unwrap(), deck::to_deck(&["7h", "7d"]).unwrap()];
+        let boards = [
+            deck::to_deck(&["Ah", "8c", "2d", "3s", "Jc"]).unwrap(),
+            deck::to_deck(&["7s", "8c", "2d", "3s", "Jc"]).unwrap(),
+            deck::to_deck(&["Ah", "Kh", "Qh", "Jh", "Th"]).unwrap(),
+        ];
+        let equity = compare_player_hands(players, boards.into_iter(), &analyser::analyse);
+        assert_eq!(equity.players[0].wins, 1);
+        assert_eq!(equity.players[1].wins, 1);
+        assert_eq!(equity.players[0].ties, 1);
+
+        let json = serde_json::to_string(&equity).unwrap();
+        assert!(json.starts_with(r#"{"boards":3,"players":[{"hand":["Ks","As"],"wins":1,"ties":1,"equity":0.5}"#));
+        assert_eq!(serde_json::from_str::<Equity>(&json).unwrap(), equity);
     }
 }
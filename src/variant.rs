@@ -0,0 +1,104 @@
+use crate::deck::{CardSet, Deck, Rank, FULL_DECK};
+use crate::hand::{Category, Hand};
+use std::cmp::Ordering;
+
+// Rules that change which cards are in play and how hands rank against each other
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Variant {
+    #[default]
+    Holdem,
+    // 6+ Hold'em without 2s to 5s: A-6-7-8-9 is the lowest straight and a flush beats a full house
+    ShortDeck,
+    // Short deck rule set where three of a kind also beats a straight
+    ShortDeckTripsBeatStraights,
+}
+
+use Category::*;
+
+const HOLDEM_ORDER: [Category; 9] = [HighCard, Pair, TwoPairs, Set, Straight, Flush, FullHouse, Quads, StraightFlush];
+const SHORT_DECK_ORDER: [Category; 9] = [HighCard, Pair, TwoPairs, Set, Straight, FullHouse, Flush, Quads, StraightFlush];
+const TRIPS_BEAT_STRAIGHTS_ORDER: [Category; 9] = [HighCard, Pair, TwoPairs, Straight, Set, FullHouse, Flush, Quads, StraightFlush];
+
+// One card of the given ranks in every suit
+const EVERY_SUIT: Deck = 1 | 1 << 13 | 1 << 26 | 1 << 39;
+
+impl Variant {
+    // Short deck plays without the 2s to 5s
+    pub fn lowest_rank(self) -> Rank {
+        match self {
+            Variant::Holdem => Rank::Two,
+            _ => Rank::Six,
+        }
+    }
+
+    // Cards that are removed from the deck before dealing
+    pub fn dead_cards(self) -> CardSet {
+        let ranks: Deck = (1 << self.lowest_rank().index()) - 1;
+        CardSet::from_deck(ranks * EVERY_SUIT)
+    }
+
+    pub fn deck(self) -> CardSet {
+        CardSet::from_deck(FULL_DECK) - self.dead_cards()
+    }
+
+    // Rank mask of the ace low straight together with its top card
+    // The ace plays below the four lowest ranks of the deck
+    pub(crate) fn wheel(self) -> (u16, Rank) {
+        let lowest = self.lowest_rank().index();
+        (1 << Rank::Ace.index() | 0xf << lowest, Rank::ALL[lowest as usize + 3])
+    }
+
+    // Categories from the weakest to the strongest
+    pub fn category_order(self) -> [Category; 9] {
+        match self {
+            Variant::Holdem => HOLDEM_ORDER,
+            Variant::ShortDeck => SHORT_DECK_ORDER,
+            Variant::ShortDeckTripsBeatStraights => TRIPS_BEAT_STRAIGHTS_ORDER,
+        }
+    }
+
+    pub fn category_strength(self, category: Category) -> u8 {
+        self.category_order().iter().position(|c| *c == category).unwrap() as u8
+    }
+
+    // Hands of different categories are ordered by the variant, hands of the same category as usual
+    pub fn compare(self, a: &Hand, b: &Hand) -> Ordering {
+        self.category_strength(a.category()).cmp(&self.category_strength(b.category()))
+            .then_with(|| a.cmp(b))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::deck::{Rank::*, Suit};
+
+    #[test]
+    fn test_short_deck_composition() {
+        let deck = Variant::ShortDeck.deck();
+        assert_eq!(deck.len(), 36);
+        assert!(deck.iter().all(|card| card.rank() >= Six));
+        assert_eq!(Variant::Holdem.deck().len(), 52);
+    }
+
+    #[test]
+    fn test_wheel() {
+        assert_eq!(Variant::Holdem.wheel(), (0x100f, Five));
+        assert_eq!(Variant::ShortDeck.wheel(), (0x10f0, Nine));
+        assert_eq!(Variant::ShortDeckTripsBeatStraights.wheel(), (0x10f0, Nine));
+    }
+
+    #[test]
+    fn test_category_ordering() {
+        let flush = Hand::flush(&[Ace, Jack, Nine, Eight, Six], Suit::Hearts);
+        let fullhouse = Hand::fullhouse(Six, Seven);
+        let set = Hand::set(Six, &[Ace, King]);
+        let straight = Hand::straight(Ace);
+
+        assert_eq!(Variant::Holdem.compare(&flush, &fullhouse), Ordering::Less);
+        assert_eq!(Variant::ShortDeck.compare(&flush, &fullhouse), Ordering::Greater);
+        assert_eq!(Variant::ShortDeck.compare(&set, &straight), Ordering::Less);
+        assert_eq!(Variant::ShortDeckTripsBeatStraights.compare(&set, &straight), Ordering::Greater);
+        assert_eq!(Variant::ShortDeck.compare(&straight, &Hand::straight(Nine)), Ordering::Greater);
+    }
+}